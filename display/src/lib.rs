@@ -1,18 +1,90 @@
 #![no_std]
-#![no_main]
+#![cfg_attr(not(test), no_main)]
 #![feature(type_alias_impl_trait)]
 
 use defmt::export::char;
+#[cfg(feature = "stm32")]
 use embassy_stm32::{self, gpio::{Level, Output, Speed}, into_ref, Peripheral};
+#[cfg(feature = "stm32")]
 use embassy_stm32::gpio::{AnyPin, Flex, Pin, Pull};
 mod fonts;
-use fonts::Symbols;
+mod mock;
+pub use fonts::Symbols;
 use fonts::Symbols::*;
+pub use mock::RecordingDisplay;
 
+/// A row of seven-segment digits, each with an LED above it. Position `2*i`
+/// is digit `i` and `2*i+1` its LED, lit by any character but `' '`.
+pub trait SegmentDisplay {
+    /// Digits across all chips.
+    fn digits(&self) -> usize;
+
+    fn turn_on_display(&mut self, brightness: u8);
+
+    fn turn_off_display(&mut self);
+
+    fn clean_display(&mut self);
+
+    fn print_char(&mut self, position: usize, character: char);
+
+    /// Sends `segments` as they are, bit layout as in `Symbols`.
+    fn print_segments(&mut self, position: usize, segments: u8);
+}
+
+/// Segment byte `print_char` sends for `character`.
+pub fn segments(character: char) -> u8 {
+    let val: Symbols = match character.to_ascii_lowercase() {
+        '0' => { SIM_0 }
+        '1' => { SIM_1 }
+        '2' => { SIM_2 }
+        '3' => { SIM_3 }
+        '4' => { SIM_4 }
+        '5' => { SIM_5 }
+        '6' => { SIM_6 }
+        '7' => { SIM_7 }
+        '8' => { SIM_8 }
+        '9' => { SIM_9 }
+        'a' => { SIM_A }
+        'b' => { SIM_b }
+        'c' => { SIM_C }
+        'd' => { SIM_d }
+        'e' => { SIM_E }
+        'f' => { SIM_F }
+        'g' => { SIM_G }
+        'h' => { SIM_H }
+        'i' => { SIM_I }
+        'j' => { SIM_J }
+        'k' => { SIM_K }
+        'l' => { SIM_L }
+        'm' => { SIM_M }
+        'n' => { SIM_N }
+        'o' => { SIM_0 }
+        'p' => { SIM_P }
+        'q' => { SIM_Q }
+        'r' => { SIM_R }
+        's' => { SIM_5 }
+        't' => { SIM_T }
+        'u' => { SIM_U }
+        'v' => { SIM_V }
+        'w' => { SIM_W }
+        'x' => { SIM_X }
+        'y' => { SIM_Y }
+        'z' => { SIM_2 }
+        '-' => { LINE }
+        '_' => { BOTTOM_LINE }
+        'B' => { SIM_B }
+        'D' => { SIM_D }
+        _ => { EMPTY }
+    };
+    return val as u8;
+}
+
+#[cfg(feature = "stm32")]
 fn make_bin(num: u8) -> [u8; 8]{
     return [(num>=128) as u8, ((num%128)>=64)as u8, ((num%64)>=32)as u8, ((num%32)>=16)as u8, ((num%16)>=8)as u8, ((num%8)>=4)as u8, ((num%4)>=2) as u8, (num%2)]
 }
 
+#[cfg(feature = "stm32")]
 pub struct LedAndKey<'d, const DIS: usize, CLK: Pin, DIO: Pin>{
     stb: [Output<'d, AnyPin>; DIS],
     clk: Output<'d, CLK>,
@@ -21,11 +93,13 @@ pub struct LedAndKey<'d, const DIS: usize, CLK: Pin, DIO: Pin>{
     b_skin: bool,
     d_skin: bool,
 }
+#[cfg(feature = "stm32")]
 fn init_stb<'d>(p: AnyPin) -> Output<'d, AnyPin>{
     into_ref!(p);
     Output::new(p, Level::High, Speed::Low)
 }
 
+#[cfg(feature = "stm32")]
 impl <'d, const DIS: usize, CLK: Pin, DIO: Pin> LedAndKey <'d, DIS, CLK, DIO> {
     pub fn new(s: [AnyPin; DIS], c:CLK, d:DIO) -> LedAndKey<'d, DIS, CLK, DIO>{
         let mut clka = Output::new(c, Level::Low, Speed::Low);
@@ -94,50 +168,14 @@ impl <'d, const DIS: usize, CLK: Pin, DIO: Pin> LedAndKey <'d, DIS, CLK, DIO> {
 
     pub fn print_char(&mut self, position: usize, character: char){
         self.move_cursor(position);
-        let mut val: Symbols = match character.to_ascii_lowercase() {
-            '0' => { SIM_0 }
-            '1' => { SIM_1 }
-            '2' => { SIM_2 }
-            '3' => { SIM_3 }
-            '4' => { SIM_4 }
-            '5' => { SIM_5 }
-            '6' => { SIM_6 }
-            '7' => { SIM_7 }
-            '8' => { SIM_8 }
-            '9' => { SIM_9 }
-            'a' => { SIM_A }
-            'b' => { SIM_b }
-            'c' => { SIM_C }
-            'd' => { SIM_d }
-            'e' => { SIM_E }
-            'f' => { SIM_F }
-            'g' => { SIM_G }
-            'h' => { SIM_H }
-            'i' => { SIM_I }
-            'j' => { SIM_J }
-            'k' => { SIM_K }
-            'l' => { SIM_L }
-            'm' => { SIM_M }
-            'n' => { SIM_N }
-            'o' => { SIM_0 }
-            'p' => { SIM_P }
-            'q' => { SIM_Q }
-            'r' => { SIM_R }
-            's' => { SIM_5 }
-            't' => { SIM_T }
-            'u' => { SIM_U }
-            'v' => { SIM_V }
-            'w' => { SIM_W }
-            'x' => { SIM_X }
-            'y' => { SIM_Y }
-            'z' => { SIM_2 }
-            '-' => { LINE }
-            '_' => { BOTTOM_LINE }
-            'B' => { SIM_B }
-            'D' => { SIM_D }
-            _ => { EMPTY }
-        };
-        self.send_byte(make_bin(val as u8));
+        self.send_byte(make_bin(segments(character)));
+        self.pos += 1;
+    }
+
+    /// Sends `segments` as they are, bit layout as in `Symbols`.
+    pub fn print_segments(&mut self, position: usize, segments: u8){
+        self.move_cursor(position);
+        self.send_byte(make_bin(segments));
         self.pos += 1;
     }
 
@@ -210,3 +248,18 @@ impl <'d, const DIS: usize, CLK: Pin, DIO: Pin> LedAndKey <'d, DIS, CLK, DIO> {
     }
     */
 }
+
+#[cfg(feature = "stm32")]
+impl <'d, const DIS: usize, CLK: Pin, DIO: Pin> SegmentDisplay for LedAndKey <'d, DIS, CLK, DIO> {
+    fn digits(&self) -> usize { return 8 * DIS; }
+
+    fn turn_on_display(&mut self, brightness: u8){ LedAndKey::turn_on_display(self, brightness); }
+
+    fn turn_off_display(&mut self){ LedAndKey::turn_off_display(self); }
+
+    fn clean_display(&mut self){ LedAndKey::clean_display(self); }
+
+    fn print_char(&mut self, position: usize, character: char){ LedAndKey::print_char(self, position, character); }
+
+    fn print_segments(&mut self, position: usize, segments: u8){ LedAndKey::print_segments(self, position, segments); }
+}
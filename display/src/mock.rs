@@ -0,0 +1,53 @@
+use crate::{segments, SegmentDisplay};
+
+/// Display that only remembers what was drawn, for driving the UI on the
+/// host. `N` is the number of digits.
+pub struct RecordingDisplay<const N: usize> {
+    digits: [u8; N],
+    leds: [bool; N],
+    brightness: Option<u8>,
+}
+
+impl<const N: usize> RecordingDisplay<N> {
+    pub fn new() -> Self { Self { digits: [0; N], leds: [false; N], brightness: None } }
+
+    /// Segment byte digit `index` shows, as in `Symbols`.
+    pub fn segments(&self, index: usize) -> u8 { return self.digits[index]; }
+
+    /// Whether digit `index` shows `character`, as `print_char` draws it.
+    pub fn shows(&self, index: usize, character: char) -> bool { return self.digits[index] == segments(character); }
+
+    pub fn led(&self, index: usize) -> bool { return self.leds[index]; }
+
+    /// Brightness while turned on, `None` while off.
+    pub fn brightness(&self) -> Option<u8> { return self.brightness; }
+}
+
+impl<const N: usize> Default for RecordingDisplay<N> {
+    fn default() -> Self { return Self::new(); }
+}
+
+impl<const N: usize> SegmentDisplay for RecordingDisplay<N> {
+    fn digits(&self) -> usize { return N; }
+
+    fn turn_on_display(&mut self, brightness: u8) { self.brightness = Some(brightness); }
+
+    fn turn_off_display(&mut self) { self.brightness = None; }
+
+    fn clean_display(&mut self) {
+        self.digits = [0; N];
+        self.leds = [false; N];
+    }
+
+    fn print_char(&mut self, position: usize, character: char) {
+        if position / 2 >= N { return; }
+        if position % 2 == 0 { self.digits[position / 2] = segments(character); }
+        else { self.leds[position / 2] = character != ' '; }
+    }
+
+    fn print_segments(&mut self, position: usize, segments: u8) {
+        if position / 2 >= N { return; }
+        if position % 2 == 0 { self.digits[position / 2] = segments; }
+        else { self.leds[position / 2] = segments != 0; }
+    }
+}
@@ -0,0 +1,116 @@
+use core::ops::Range;
+use display::SegmentDisplay;
+use keyboard::KeySource;
+use crate::DisplayAndKeyboard;
+
+/// Contents of one digit of the display.
+#[derive(Copy, Clone, PartialEq, Eq, Default, defmt::Format)]
+pub enum Cell {
+    #[default]
+    Empty,
+    /// Decimal digit, `0..=9`.
+    Digit(u8),
+    /// Any other character `print_char` knows.
+    Char(char),
+    /// Raw segment byte, as in `display::Symbols`.
+    Glyph(u8),
+}
+
+impl Cell {
+    /// `' '` is `Empty` and decimal digits are `Digit`; everything else,
+    /// letters included, stays a `Char`.
+    pub fn from_char(character: char) -> Cell {
+        match character {
+            ' ' => Cell::Empty,
+            '0'..='9' => Cell::Digit(character as u8 - b'0'),
+            _ => Cell::Char(character),
+        }
+    }
+
+    /// Character that draws the cell, `None` for glyphs and digits above 9.
+    pub fn to_char(self) -> Option<char> {
+        match self {
+            Cell::Empty => Some(' '),
+            Cell::Digit(d) if d < 10 => Some((b'0' + d) as char),
+            Cell::Digit(_) | Cell::Glyph(_) => None,
+            Cell::Char(c) => Some(c),
+        }
+    }
+
+    /// Value of a `Digit` cell.
+    pub fn digit(self) -> Option<u8> {
+        match self {
+            Cell::Digit(d) => Some(d),
+            _ => None,
+        }
+    }
+
+    pub fn is_empty(self) -> bool { return self == Cell::Empty; }
+}
+
+/// Everything on the display, to put back after a widget drew over it.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Screen<const BUTD: usize> {
+    cells: [Cell; BUTD],
+    leds: [bool; BUTD],
+}
+
+impl<const BUTD: usize, D: SegmentDisplay, K: KeySource> DisplayAndKeyboard<BUTD, D, K> {
+    /// What cell `index` shows, as last drawn.
+    pub fn cell(&self, index: usize) -> Cell { return self.cells[index]; }
+
+    pub fn cells(&self) -> &[Cell; BUTD] { return &self.cells; }
+
+    /// Draws `cell` at `index`.
+    pub fn set_cell(&mut self, index: usize, cell: Cell) {
+        match cell {
+            Cell::Glyph(segments) => { self.display.print_segments(index*2, segments); }
+            _ => { self.display.print_char(index*2, cell.to_char().unwrap_or(' ')); }
+        }
+        self.cells[index] = cell;
+    }
+
+    /// Whether the LED above cell `index` is lit.
+    pub fn led(&self, index: usize) -> bool { return self.leds[index]; }
+
+    pub fn set_led(&mut self, index: usize, on: bool) {
+        self.display.print_char(index*2+1, if on { 'B' } else { ' ' });
+        self.leds[index] = on;
+    }
+
+    pub fn set_leds(&mut self, cells: Range<usize>, on: bool) {
+        for index in cells { self.set_led(index, on); }
+    }
+
+    /// Snapshot of the display. Widgets that take over the whole display,
+    /// `confirm` and `virtual_keyboard`, `restore` it when they are done.
+    pub fn screen(&self) -> Screen<BUTD> { return Screen { cells: self.cells, leds: self.leds }; }
+
+    /// Redraws every cell and LED of `screen`.
+    pub fn restore(&mut self, screen: &Screen<BUTD>) {
+        for index in 0..BUTD {
+            self.set_cell(index, screen.cells[index]);
+            self.set_led(index, screen.leds[index]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chars_round_trip() {
+        for ch in [' ', '0', '7', '9', 'a', 'f', 'g', '-'] {
+            assert_eq!(Cell::from_char(ch).to_char(), Some(ch));
+        }
+        assert!(Cell::from_char('5') == Cell::Digit(5));
+        assert!(Cell::from_char('b') == Cell::Char('b'));
+    }
+
+    #[test]
+    fn only_decimal_digits_are_drawn() {
+        assert_eq!(Cell::Digit(10).to_char(), None);
+        assert_eq!(Cell::Glyph(0xFF).to_char(), None);
+    }
+}
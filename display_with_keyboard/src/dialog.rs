@@ -0,0 +1,62 @@
+use display::SegmentDisplay;
+use embassy_time::Duration;
+use keyboard::{Key, KeySource};
+use crate::{Cell, DisplayAndKeyboard};
+
+impl<const BUTD: usize, D: SegmentDisplay, K: KeySource> DisplayAndKeyboard<BUTD, D, K> {
+    /// Asks `prompt` and returns the index of the chosen option.
+    ///
+    /// The prompt starts at the first cell and the options share the cells
+    /// after it with equal gaps, so `confirm("quit", &["yes", "no"], ..)`
+    /// reads `quit    yes   no`. The cursor LEDs start on `default`;
+    /// `<-`/`->` move them, `Ent` chooses, digit `n` chooses option `n`, and
+    /// `Esc` or `timeout` without a key choose `default`. A `default` past
+    /// the last option means the last option. Takes over the whole display,
+    /// see `screen`.
+    pub fn confirm(&mut self, prompt: &str, options: &[&str], default: usize, timeout: Option<Duration>) -> usize {
+        let saved = self.screen();
+        let mut spans: [(usize, usize); 3] = [(0, 0); 3];
+        let count = options.len().min(spans.len());
+        let start = prompt.len() + 1;
+        let total: usize = options[..count].iter().map(|o| o.len()).sum();
+        let gap = BUTD.saturating_sub(start + total) / count.max(1);
+        let mut at = start + gap;
+        for i in 0..count {
+            spans[i] = (at, at + options[i].len());
+            at += options[i].len() + gap;
+        }
+        self.clean_display();
+        for (i, ch) in prompt.chars().enumerate() {
+            if i < BUTD { self.set_cell(i, Cell::from_char(ch)); }
+        }
+        for i in 0..count {
+            for (j, ch) in options[i].chars().enumerate() {
+                if spans[i].0 + j < BUTD { self.set_cell(spans[i].0 + j, Cell::from_char(ch)); }
+            }
+        }
+        let default = default.min(count.saturating_sub(1));
+        let mut position = default;
+        let res = loop {
+            for cell in 0..BUTD {
+                self.set_led(cell, cell >= spans[position].0 && cell < spans[position].1);
+            }
+            let key = match timeout {
+                Some(timeout) => match self.wait_key_timeout(timeout) {
+                    Some(key) => key,
+                    None => break default,
+                },
+                None => self.get_pressed(),
+            };
+            match key {
+                Key::Left if position > 0 => { position -= 1; }
+                Key::Right if position + 1 < count => { position += 1; }
+                Key::Ent => { break position; }
+                Key::Esc => { break default; }
+                Key::Digit(n) if n >= 1 && (n as usize) <= count => { break n as usize - 1; }
+                _ => {}
+            }
+        };
+        self.restore(&saved);
+        return res;
+    }
+}
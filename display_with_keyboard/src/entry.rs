@@ -0,0 +1,263 @@
+use display::SegmentDisplay;
+use embassy_time::{Duration, Instant};
+use keyboard::{Key, KeySource};
+use crate::{Cell, DisplayAndKeyboard, VirtualKeyboard};
+
+/// Symbols an entry accepts, in order, and the value of the first one.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    symbols: &'static str,
+    first: u8,
+}
+
+impl Alphabet {
+    /// `symbols` must be ASCII; symbol `i` has the value `first + i`.
+    pub const fn new(symbols: &'static str, first: u8) -> Self { Self { symbols, first } }
+
+    pub fn len(&self) -> u8 { return self.symbols.len() as u8; }
+
+    pub fn is_empty(&self) -> bool { return self.symbols.is_empty(); }
+
+    pub fn first(&self) -> u8 { return self.first; }
+
+    pub fn last(&self) -> u8 { return self.first + self.len() - 1; }
+
+    pub fn symbol(&self, value: u8) -> Option<char> {
+        if value < self.first { return None; }
+        return self.symbols.as_bytes().get((value - self.first) as usize).map(|b| *b as char);
+    }
+
+    pub fn value(&self, symbol: char) -> Option<u8> {
+        return self.symbols.chars().position(|c| c == symbol).map(|i| i as u8 + self.first);
+    }
+}
+
+pub const DECIMAL: Alphabet = Alphabet::new("0123456789", 0);
+pub const HEX: Alphabet = Alphabet::new("0123456789abcdef", 0);
+/// The 16 symbols of the memory game, valued 1 to 16.
+pub const GAME: Alphabet = Alphabet::new("123456789abcdefg", 1);
+pub const LETTERS: Alphabet = Alphabet::new("abcdefghijklmnopqrstuvwxyz", 1);
+
+/// How often `entry_until` reports the time left.
+const TICK: Duration = Duration::from_millis(100);
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Submitted,
+    Cancelled,
+}
+
+/// Values entered into a field, in alphabet values rather than characters.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Entry<const N: usize> {
+    values: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> Entry<N> {
+    pub const fn new() -> Self { Self { values: [0; N], len: 0 } }
+
+    pub fn as_slice(&self) -> &[u8] { return &self.values[..self.len]; }
+
+    pub fn len(&self) -> usize { return self.len; }
+
+    pub fn is_empty(&self) -> bool { return self.len == 0; }
+
+    /// Reads the values as digits of a number in `base`, most significant first.
+    pub fn to_number(&self, base: u64) -> u64 {
+        let mut res: u64 = 0;
+        for value in self.as_slice() {
+            res = res.wrapping_mul(base).wrapping_add(*value as u64);
+        }
+        return res;
+    }
+
+    fn insert(&mut self, at: usize, value: u8) {
+        let mut i = self.len;
+        while i > at {
+            self.values[i] = self.values[i - 1];
+            i -= 1;
+        }
+        self.values[at] = value;
+        self.len += 1;
+    }
+
+    fn remove(&mut self, at: usize) {
+        for i in at..self.len - 1 { self.values[i] = self.values[i + 1]; }
+        self.len -= 1;
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct EntryResult<const N: usize> {
+    pub value: Entry<N>,
+    pub outcome: Outcome,
+}
+
+/// Editable row of symbols.
+///
+/// The field sits right-aligned in the `max` cells starting at `start`, and
+/// the cell under the cursor has its LED lit. Keys:
+/// - digits enter their symbol; for alphabets without `0`, the `0` key
+///   makes the next digit `n` enter the `n`-th letter instead (`0 1` is `a`)
+/// - `^`/`v` step the symbol under the cursor through the alphabet
+/// - `<-`/`->` move the cursor, `F1` toggles insert and overwrite
+/// - `*` deletes before the cursor, `#` clears the field
+/// - `F2` picks a symbol on a `VirtualKeyboard`
+/// - `Ent` submits once at least `min` symbols are in, `Esc` cancels
+///
+/// The field keeps its contents between runs, so a cancelled entry can be
+/// resumed by running it again.
+pub struct EntryField<const N: usize> {
+    alphabet: Alphabet,
+    start: usize,
+    max: usize,
+    min: usize,
+    insert: bool,
+    letters: bool,
+    cursor: usize,
+    value: Entry<N>,
+}
+
+impl<const N: usize> EntryField<N> {
+    /// Field for up to `N` symbols of `alphabet` at the first cell.
+    pub fn new(alphabet: Alphabet) -> Self {
+        Self { alphabet, start: 0, max: N, min: 0, insert: true, letters: false, cursor: 0, value: Entry::new() }
+    }
+
+    /// Limits the field to `max` symbols, at most `N`.
+    pub fn max(mut self, max: usize) -> Self { self.max = max.min(N); self }
+
+    /// Symbols needed before `Ent` is accepted.
+    pub fn min(mut self, min: usize) -> Self { self.min = min; self }
+
+    /// First display cell of the field.
+    pub fn at(mut self, start: usize) -> Self { self.start = start; self }
+
+    /// Starts in overwrite rather than insert mode.
+    pub fn overwrite(mut self) -> Self { self.insert = false; self }
+
+    pub fn value(&self) -> &Entry<N> { return &self.value; }
+
+    pub fn alphabet(&self) -> Alphabet { return self.alphabet; }
+
+    pub fn clear(&mut self) {
+        self.value = Entry::new();
+        self.cursor = 0;
+        self.letters = false;
+    }
+
+    /// Puts `value` at the cursor and moves past it.
+    pub fn put(&mut self, value: u8) {
+        if self.alphabet.symbol(value).is_none() { return; }
+        if self.cursor < self.value.len && !self.insert {
+            self.value.values[self.cursor] = value;
+        } else if self.value.len < self.max {
+            self.value.insert(self.cursor, value);
+        } else {
+            return;
+        }
+        self.cursor += 1;
+    }
+
+    fn step(&mut self, up: bool) {
+        if self.cursor >= self.value.len { return; }
+        let value = self.value.values[self.cursor];
+        self.value.values[self.cursor] = match up {
+            true if value >= self.alphabet.last() => self.alphabet.first(),
+            true => value + 1,
+            false if value <= self.alphabet.first() => self.alphabet.last(),
+            false => value - 1,
+        };
+    }
+
+    /// Applies one key. `F2` is left to `DisplayAndKeyboard::entry`, which
+    /// opens the virtual keyboard.
+    pub fn handle(&mut self, key: Key) -> Option<Outcome> {
+        let letters = self.letters;
+        self.letters = false;
+        match key {
+            Key::Ent if self.value.len >= self.min => { return Some(Outcome::Submitted); }
+            Key::Esc => { return Some(Outcome::Cancelled); }
+            Key::Left if self.cursor > 0 => { self.cursor -= 1; }
+            Key::Right if self.cursor < self.value.len => { self.cursor += 1; }
+            Key::Up => { self.step(true); }
+            Key::Down => { self.step(false); }
+            Key::F1 => { self.insert = !self.insert; }
+            Key::Star if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.value.remove(self.cursor);
+            }
+            Key::Hash => { self.clear(); }
+            Key::Digit(0) if self.alphabet.value('0').is_none() => { self.letters = !letters; }
+            Key::Digit(d) => {
+                let symbol = if letters { (b'a' + d - 1) as char } else { (b'0' + d) as char };
+                if let Some(value) = self.alphabet.value(symbol) { self.put(value); }
+            }
+            _ => {}
+        }
+        return None;
+    }
+
+    fn cell(&self, index: usize) -> usize {
+        return self.start + self.max - self.shown() + index;
+    }
+
+    /// Cells in use: the value, plus one for a pending letter at the end.
+    fn shown(&self) -> usize {
+        if self.letters && self.cursor == self.value.len && self.value.len < self.max { return self.value.len + 1; }
+        return self.value.len;
+    }
+}
+
+impl<const BUTD: usize, D: SegmentDisplay, K: KeySource> DisplayAndKeyboard<BUTD, D, K> {
+    /// Runs `field` until it is submitted or cancelled.
+    pub fn entry<const N: usize>(&mut self, field: &mut EntryField<N>) -> EntryResult<N> {
+        loop {
+            self.draw_entry(field);
+            let key = self.get_pressed();
+            if let Some(res) = self.entry_key(field, key) { return res; }
+        }
+    }
+
+    /// Like `entry`, but gives up at `deadline` with `None`. `tick` is called
+    /// with the time left about every 100 ms, to show it.
+    pub fn entry_until<const N: usize>(&mut self, field: &mut EntryField<N>, deadline: Instant, mut tick: impl FnMut(&mut Self, Duration)) -> Option<EntryResult<N>> {
+        loop {
+            self.draw_entry(field);
+            let now = Instant::now();
+            if now >= deadline { return None; }
+            tick(self, deadline - now);
+            let key = match self.wait_key_timeout((deadline - now).min(TICK)) { Some(key) => key, None => continue };
+            if let Some(res) = self.entry_key(field, key) { return Some(res); }
+        }
+    }
+
+    /// Applies `key` to `field`, opening the virtual keyboard for `F2`.
+    fn entry_key<const N: usize>(&mut self, field: &mut EntryField<N>, key: Key) -> Option<EntryResult<N>> {
+        if key == Key::F2 {
+            let mut keyboard = VirtualKeyboard::new(field.alphabet);
+            if let Some(value) = self.virtual_keyboard(&mut keyboard).and_then(|s| field.alphabet.value(s)) { field.put(value); }
+            return None;
+        }
+        let outcome = field.handle(key)?;
+        self.set_leds(field.start..field.start + field.max, false);
+        return Some(EntryResult { value: field.value, outcome });
+    }
+
+    fn draw_entry<const N: usize>(&mut self, field: &EntryField<N>) {
+        let first = field.cell(0);
+        for cell in field.start..first { self.set_cell(cell, Cell::Empty); }
+        for i in 0..field.shown() {
+            let symbol = match field.value.as_slice().get(i) {
+                Some(value) => field.alphabet.symbol(*value).unwrap_or(' '),
+                None => '-',
+            };
+            self.set_cell(first + i, Cell::from_char(if field.letters && i == field.cursor { '-' } else { symbol }));
+        }
+        for cell in field.start..field.start + field.max {
+            let lit = field.cursor < field.value.len && cell == field.cell(field.cursor);
+            self.set_led(cell, lit);
+        }
+    }
+}
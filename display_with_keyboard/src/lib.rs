@@ -1,29 +1,48 @@
 #![no_std]
-#![no_main]
+#![cfg_attr(not(test), no_main)]
 #![feature(type_alias_impl_trait)]
 
-use display::LedAndKey;
-use keyboard::Keyboard;
-use embassy_stm32::{self, Peripheral};
-use embassy_stm32::gpio::{AnyPin, Pin, Pull};
-use embassy_stm32::time::khz;
-use embassy_time::{Duration, Timer};
-
-
-
-pub struct DisplayAndKeyboard <'d, const DIS: usize, const BUTD: usize, CLK: Pin, DIO: Pin, const ROW: usize, const COL: usize> {
-    display: LedAndKey<'d, DIS, CLK, DIO>,
-    keyboard: Keyboard<'d, ROW, COL>,
-    is_on: [u64; BUTD]
+pub use display::{RecordingDisplay, SegmentDisplay};
+#[cfg(feature = "stm32")]
+pub use display::LedAndKey;
+pub use keyboard::{CapacityError, Drive, FixedMap, Hold, Key, KeyEvent, Keymap, KeySet, KeySource, Merged, MockKeyboard, Polarity, Presses, Recorder, Replay, Scan, Step, Wiring, COLUMNS_ACTIVE_HIGH, KEYPAD_5X4, MEMBRANE_4X3, MEMBRANE_4X4};
+#[cfg(feature = "stm32")]
+pub use keyboard::{Expander, ExpanderError, I2cKeyboard, Keyboard, RotaryEncoder};
+use embassy_time::Duration;
+mod cell;
+mod dialog;
+mod entry;
+mod menu;
+mod vkeyboard;
+pub use cell::{Cell, Screen};
+pub use entry::{Alphabet, Entry, EntryField, EntryResult, Outcome, DECIMAL, GAME, HEX, LETTERS};
+pub use menu::{Editor, Item, Menu, MenuEvent};
+pub use vkeyboard::VirtualKeyboard;
+
+
+/// `BUTD` cells of `display` with `keyboard` under them. On the host,
+/// `RecordingDisplay` and `MockKeyboard` stand in for the hardware.
+pub struct DisplayAndKeyboard <const BUTD: usize, D: SegmentDisplay, K: KeySource> {
+    display: D,
+    keyboard: K,
+    cells: [Cell; BUTD],
+    leds: [bool; BUTD],
+    /// Keys already reported by `get_pressed` and the other key waits.
+    presses: Presses,
+    b_skin: bool,
+    d_skin: bool,
 }
 
-impl <'d, const DIS: usize, const BUTD: usize, CLK: Pin, DIO: Pin, const ROW: usize, const COL: usize> DisplayAndKeyboard<'d, DIS, BUTD, CLK, DIO, ROW, COL>{
-    pub fn new(s: [AnyPin; DIS], c:CLK, d:DIO, for_game: [u8; BUTD], inputs: [AnyPin; ROW], outputs: [AnyPin; COL]) -> DisplayAndKeyboard<'d, DIS, BUTD, CLK, DIO, ROW, COL>{
-        let mut display = LedAndKey::new(s, c, d);
-        let mut keyboard = Keyboard::new(inputs, outputs);
-        Self { display, keyboard, is_on: [20; BUTD]}
+impl <const BUTD: usize, D: SegmentDisplay, K: KeySource> DisplayAndKeyboard<BUTD, D, K>{
+    pub fn new(display: D, keyboard: K) -> DisplayAndKeyboard<BUTD, D, K>{
+        Self { display, keyboard, cells: [Cell::Empty; BUTD], leds: [false; BUTD], presses: Presses::new(), b_skin: false, d_skin: false }
     }
 
+    pub fn display(&self) -> &D { return &self.display; }
+
+    /// The key source, e.g. to dump a `Recorder`.
+    pub fn keyboard(&mut self) -> &mut K { return &mut self.keyboard; }
+
     pub fn turn_on_display(&mut self, brightness: u8){
         self.display.turn_on_display(brightness);
     }
@@ -34,209 +53,86 @@ impl <'d, const DIS: usize, const BUTD: usize, CLK: Pin, DIO: Pin, const ROW: us
 
     pub fn clean_display(&mut self){
         self.display.clean_display();
-        self.is_on = [20; BUTD];
+        self.cells = [Cell::Empty; BUTD];
+        self.leds = [false; BUTD];
     }
 
-    pub fn print(&mut self, position: usize, s: &str){
-        self.display.print(position, s);
+    /// Same placement as `LedAndKey::print`, drawn through `print_char` so
+    /// the cell model follows.
+    pub fn print(&mut self, mut position: usize, s: &str){
+        let digits = self.display.digits();
+        let mut count: usize = 0;
+        for ch in s.chars(){
+            if count == digits { break; }
+            let ch = ch.to_ascii_lowercase();
+            if !(ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '-' || ch == '_') { continue; }
+            position += 2; count += 1;
+            let shown = self.skinned(ch);
+            self.print_char(position, shown);
+            position %= 2*digits;
+        }
     }
 
-    fn display_send_byte(&mut self, command: [u8; 8]){ self.display.send_byte(command); }
-
     pub fn swap_b_skin(&mut self){
-        self.display.swap_b_skin();
+        self.b_skin = !self.b_skin;
     }
 
     pub fn swap_d_skin(&mut self){
-        self.display.swap_d_skin();
+        self.d_skin = !self.d_skin;
+    }
+
+    pub fn b_skin(&self) -> bool { return self.b_skin; }
+
+    pub fn d_skin(&self) -> bool { return self.d_skin; }
+
+    /// `character` as `print` shows it with the current skins.
+    pub fn skinned(&self, character: char) -> char{
+        match character {
+            'b' | 'B' if self.b_skin => 'B',
+            'd' | 'D' if self.d_skin => 'D',
+            _ => character
+        }
     }
 
     pub fn print_char(&mut self, position: usize, character: char){
         self.display.print_char(position, character);
-        if (character as u8) >= ('0' as u8) && (character as u8) <= ('9' as u8){self.is_on[position/2] = (character as u64) - ('0' as u64);}
-        else if (character as u8) >= ('a' as u8) && (character as u8) <= ('f' as u8) { self.is_on[position/2] = (character as u64) - ('a' as u64) + 10;}
+        if position/2 >= BUTD { return; }
+        if position % 2 == 0 { self.cells[position/2] = Cell::from_char(character); }
+        else { self.leds[position/2] = character != ' '; }
     }
 
-    pub fn get_pressed(&mut self) -> u8 {
-        let mut c: usize = 0; let mut d: usize = 0;
-        let mut flag1: bool = true; let mut flag2: bool = true;
-        let mut buttons: [[u8;  ROW]; COL] = [[0; ROW]; COL];
-        let mut tmp1: [[u8;  ROW]; COL] = [[0; ROW]; COL];
-        let mut tmp2: [[u8;  ROW]; COL] = [[0; ROW]; COL];
-        let mut pressed: u8 = 0;
-        while flag1 {
-            buttons = self.keyboard.read_key();
-            c = 0;
-            for row in buttons {
-                d = 0;
-                for but in row {
-                    if but == 1 { flag1 = false; }
-                    tmp1[d][c] = but;
-                    c += 1;
-                }
-                d += 1;
-            }
-        }
-        while flag2 {
-            buttons = self.keyboard.read_key();
-            c = 0;
-            flag2 = false;
-            for row in buttons {
-                d = 0;
-                for but in row {
-                    if but == 1 { flag2 = true; };
-                    tmp2[d][c] = tmp1[d][c];
-                    tmp1[d][c] = but;
-                    c += 1;
-                }
-                d += 1;
-            }
-        }
-        c = 1;
-        for row in tmp2 {
-            for but in row {
-                if but == 1 { pressed = c as u8;break; }
-                c += 1;
-            }
-        }
-        return pressed;
+    /// Waits for a press and its release, for chords; see
+    /// `KeySource::wait_press`.
+    pub fn get_scan(&mut self) -> Scan {
+        let res = self.keyboard.wait_press();
+        // Every key is up again.
+        self.presses = Presses::new();
+        return res;
     }
 
-    /*
-    0 - F1, 5 - F2, 10 - #,  15 - *,
-    1 - 1,  6 - 2,  11 - 3,  16 - ^,
-    2 - 4,  7 - 5,  12 - 6,  17 - v,
-    3 - 7,  8 - 8,  13 - 9,  18 - Esc,
-    4 - <-, 9 - 0,  14 - ->, 19 - Ent,
-     */
-    pub fn default_print(&mut self, max: u8, mut thing_for_small_random: u64) -> [u64; 18]{
-        let mut position: usize = 16 ; let mut count: usize = 0; let mut tmp: usize = 0;
-        let mut blinking: [u8; 16] = [0; 16];
-        let mut character= '0';
-        let mut f2: bool = false; let mut zero: bool = false;
-        let mut pressed: u8 = 0;
-        let mut res: [u64; 18] = [0; 18]; count = 0;
-        self.reprint();
-        loop {
-            pressed = self.get_pressed();
-            match pressed {
-                20 => { break; }
-                19 => { res[17] = 1; break; }
-                1 if f2 => {
-                    f2 = false;
-                    self.reprint();
-                    blinking = [0; 16];
-                    count = tmp; position = BUTD;
-                    self.cursor(blinking);
-                }
-                6 if !f2 => {
-                    self.make_keyboard();
-                    f2 = true;
-                    tmp = count;
-                    count = BUTD; position = 0;
-                    blinking = [0; 16]; blinking[0] = 1;
-                    self.cursor(blinking);
-                }
-                5 if position+count > BUTD && !zero => {
-                    blinking[position-1] = 1;
-                    if position<BUTD { blinking[position] = 0; }
-                    position -= 1;
-                    self.cursor(blinking);
-                }
-                15 if ((position<BUTD && !f2) || (position+1<BUTD && f2)) && !zero => {
-                    position += 1;
-                    blinking[position-1] = 0;
-                    if position<16 { blinking[position] = 1; }
-                    self.cursor(blinking);
-                }
-                p if p%5>1 && p<15 && !f2 => {
-                    if zero {
-                        character = (((pressed%5-2)*3+(pressed-pressed%5)/5) + ('a' as u8)) as char;
-                        if (character as u8)>('g' as u8) { character = 'g'; }
-                    }
-                    else { character = ((pressed%5-2)*3+(pressed-pressed%5)/5 + ('1' as u8)) as char; }
-                    if position<BUTD {
-                        self.print_char(position*2, character);
-                    } else if (count as u8) < max {
-                        if !zero {
-                            self.change_is_on(1);
-                            self.reprint();
-                        }
-                        self.print_char(30, character);
-                        count += 1;
-                    }
-                    zero = false;
-                }
-                10 if f2 => {
-                    self.change_is_on(1);
-                    self.is_on[BUTD-1] = (position+1) as u64;
-                    tmp += 1;
-                }
-                10 if !f2 => {
-                    if position == BUTD && (count as u8)<max {
-                        self.change_is_on(1);
-                        self.reprint();
-                        zero = true;
-                        self.print_char(30, '-');
-                    }
-                    else if position < BUTD {
-                        zero = true;
-                        self.print_char(2*position, '-');
-                    }
-                }
-                11 => {
-                    self.is_on = [20; BUTD];
-                    if !f2 {self.reprint();}
-                    count = 0; tmp = 0;
-                }
-                16 if count>0 => {
-                    self.change_is_on(-1);
-                    if !f2 { self.reprint(); }
-                    count -= 1;
-                }
-                _ => {}
-            }
-            thing_for_small_random+=1; thing_for_small_random %= 1e15 as u64;
-        }
-        while count<16 {
-            res[count] = self.is_on[count];
-            count += 1;
-        }
-        res[16] = thing_for_small_random;
-        return res;
+    /// The next key pressed, see `Presses::next`.
+    pub fn get_pressed(&mut self) -> Key {
+        return self.keyboard.wait_key(&mut self.presses);
     }
 
-    fn make_keyboard(&mut self){ self.print(0,"123456789abcdefg"); }
+    /// Waits up to `timeout` for a key, `None` on timeout.
+    pub fn wait_key_timeout(&mut self, timeout: Duration) -> Option<Key> {
+        return self.keyboard.wait_key_timeout(&mut self.presses, timeout);
+    }
 
-    fn change_is_on(&mut self, edit: isize){
-        let mut i: isize = 0; let m: isize = edit;
-        if edit > 0 {
-            while (i + m) < 16 {
-                if self.is_on[(i + m) as usize] < 20 { self.is_on[i as usize] = self.is_on[(i+m) as usize]; }
-                i += 1;
-            }
-        }else{
-            i = 15;
-            while (i + m) >= 0 {
-                if self.is_on[(i + m) as usize] < 20 { self.is_on[i as usize] = self.is_on[(i+m)as usize];  }
-                else{ self.is_on[i as usize] = 20; }
-                i -= 1;
-            }
-        }
+    /// Waits for a key without blocking the executor.
+    pub async fn wait_key(&mut self) -> Key {
+        return keyboard::wait_key(&mut self.keyboard, &mut self.presses).await;
     }
 
-    fn reprint(&mut self){
-        for i in 0..BUTD {
-            if self.is_on[i] < 10 {self.print_char(i*2, (self.is_on[i] as u8 + ('0' as u8)) as char);}
-            else if self.is_on[i] < 17 { self.print_char(i*2, ((self.is_on[i]%10) as u8 + ('a' as u8)) as char); }
-            else{ self.print_char(i*2, ' '); }
-        }
+    /// Async `wait_key_timeout`; the wait can also be dropped from a `select`.
+    pub async fn wait_key_cancellable(&mut self, timeout: Duration) -> Option<Key> {
+        return keyboard::wait_key_cancellable(&mut self.keyboard, &mut self.presses, timeout).await;
     }
 
     pub fn cursor(&mut self, blinking: [u8; 16]){
         for i in 0..BUTD {
-            if blinking[i] == 1{ self.print_char(i*2+1, 'B'); }
-            else { self.print_char(i*2+1, ' '); }
+            self.set_led(i, blinking[i] == 1);
         }
     }
 
@@ -0,0 +1,204 @@
+use core::ops::Range;
+use display::SegmentDisplay;
+use keyboard::{Key, KeySource};
+use crate::{Alphabet, Cell, DisplayAndKeyboard};
+
+/// What a menu run ended with. The menu keeps its selection, so the caller
+/// handles the event and runs the same menu again.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum MenuEvent {
+    /// An `Action` item was chosen.
+    Action(u8),
+    /// The value of an item was edited.
+    Changed(u8),
+    /// `Esc` on the top level.
+    Back,
+}
+
+/// Value shown after an item's label and how it is edited.
+pub enum Editor<'a> {
+    /// No value; `Ent` reports the item.
+    Action,
+    /// Shown as `y`/`n`, flipped by `Ent`, `^` and `v`.
+    Bool(bool),
+    /// `min..=max`, shown as one symbol of `symbols` and stepped with
+    /// wrap-around by `^` and `v`.
+    Range { value: u8, min: u8, max: u8, symbols: Alphabet },
+    /// Index into `options`, cycled by `Ent`, `^` and `v`.
+    Choice { value: u8, options: &'a [&'a str] },
+    /// Opened by `Ent`, left by `Esc`.
+    Submenu(Menu<'a>),
+}
+
+impl<'a> Editor<'a> {
+    fn width(&self) -> usize {
+        match self {
+            Editor::Bool(_) | Editor::Range { .. } => 1,
+            Editor::Choice { options, .. } => options.iter().map(|o| o.len()).max().unwrap_or(0),
+            Editor::Action | Editor::Submenu(_) => 0,
+        }
+    }
+
+    /// Moves the value one step; `false` if there is nothing to step.
+    fn step(&mut self, up: bool) -> bool {
+        match self {
+            Editor::Bool(value) => { *value = !*value; }
+            Editor::Range { value, min, max, .. } => {
+                *value = match up {
+                    true if *value >= *max => *min,
+                    true => *value + 1,
+                    false if *value <= *min => *max,
+                    false => *value - 1,
+                };
+            }
+            Editor::Choice { value, options } => {
+                let count = options.len() as u8;
+                if count == 0 { return false; }
+                *value = if up { (*value + 1) % count } else { (*value + count - 1) % count };
+            }
+            Editor::Action | Editor::Submenu(_) => { return false; }
+        }
+        return true;
+    }
+}
+
+/// Menu entry: a label at cell `at`, followed by its editor's value.
+/// `id` is what events report and should be unique across nested menus.
+pub struct Item<'a> {
+    pub id: u8,
+    pub label: &'a str,
+    pub at: usize,
+    pub editor: Editor<'a>,
+}
+
+impl<'a> Item<'a> {
+    pub const fn new(id: u8, label: &'a str, at: usize, editor: Editor<'a>) -> Self { Self { id, label, at, editor } }
+
+    /// Cells taken by label and value; the cursor LEDs light all of them.
+    pub fn span(&self) -> Range<usize> {
+        return self.at..self.at + self.label.len() + self.editor.width();
+    }
+}
+
+/// Items in cursor order, which is also left-to-right order on the display.
+/// Items past the last cell scroll into view as the cursor reaches them.
+pub struct Menu<'a> {
+    items: &'a mut [Item<'a>],
+    selected: usize,
+    first: usize,
+    open: bool,
+}
+
+impl<'a> Menu<'a> {
+    pub fn new(items: &'a mut [Item<'a>]) -> Self { Self { items, selected: 0, first: 0, open: false } }
+
+    /// Item `id`, looked up through submenus too.
+    pub fn item(&self, id: u8) -> Option<&Item<'a>> {
+        for item in self.items.iter() {
+            if item.id == id { return Some(item); }
+            if let Editor::Submenu(sub) = &item.editor {
+                if let Some(found) = sub.item(id) { return Some(found); }
+            }
+        }
+        return None;
+    }
+
+    /// Value of a `Range` or `Choice` item.
+    pub fn value(&self, id: u8) -> Option<u8> {
+        match self.item(id)?.editor {
+            Editor::Range { value, .. } | Editor::Choice { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Value of a `Bool` item.
+    pub fn flag(&self, id: u8) -> Option<bool> {
+        match self.item(id)?.editor {
+            Editor::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Id of the item under the cursor.
+    pub fn selected(&self) -> u8 { return self.items[self.selected].id; }
+
+    /// Brings the selected item into view.
+    fn scroll<const BUTD: usize>(&mut self) {
+        let span = self.items[self.selected].span();
+        if span.start < self.first { self.first = span.start; }
+        if span.end > self.first + BUTD { self.first = span.end - BUTD; }
+    }
+}
+
+impl<const BUTD: usize, D: SegmentDisplay, K: KeySource> DisplayAndKeyboard<BUTD, D, K> {
+    /// Runs `menu` until something happens the caller has to know about.
+    /// `<-`/`->` move the cursor, digit `n` jumps to item `n` and chooses it,
+    /// `Ent` chooses, `^`/`v` edit and `Esc` goes back.
+    pub fn menu(&mut self, menu: &mut Menu) -> MenuEvent {
+        loop {
+            if menu.open {
+                if let Editor::Submenu(sub) = &mut menu.items[menu.selected].editor {
+                    match self.menu(sub) {
+                        MenuEvent::Back => { menu.open = false; }
+                        event => { return event; }
+                    }
+                }
+            }
+            self.draw_menu(menu);
+            let key = self.get_pressed();
+            let count = menu.items.len();
+            match key {
+                Key::Left if menu.selected > 0 => { menu.selected -= 1; }
+                Key::Right if menu.selected + 1 < count => { menu.selected += 1; }
+                Key::Up | Key::Down => {
+                    let item = &mut menu.items[menu.selected];
+                    if item.editor.step(key == Key::Up) { return MenuEvent::Changed(item.id); }
+                }
+                Key::Esc => { return MenuEvent::Back; }
+                Key::Ent => { if let Some(event) = Self::choose(menu) { return event; } }
+                Key::Digit(n) if n >= 1 && (n as usize) <= count => {
+                    menu.selected = n as usize - 1;
+                    if let Some(event) = Self::choose(menu) { return event; }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn choose(menu: &mut Menu) -> Option<MenuEvent> {
+        let item = &mut menu.items[menu.selected];
+        match item.editor {
+            Editor::Action => Some(MenuEvent::Action(item.id)),
+            Editor::Submenu(_) => { menu.open = true; None }
+            Editor::Range { .. } => None,
+            _ => { item.editor.step(true); Some(MenuEvent::Changed(item.id)) }
+        }
+    }
+
+    fn draw_menu(&mut self, menu: &mut Menu) {
+        menu.scroll::<BUTD>();
+        let first = menu.first;
+        let mut cells: [char; BUTD] = [' '; BUTD];
+        let mut put = |at: usize, ch: char| {
+            if at >= first && at < first + BUTD { cells[at - first] = ch; }
+        };
+        for item in menu.items.iter() {
+            let mut at = item.at;
+            for ch in item.label.chars() { put(at, ch); at += 1; }
+            match &item.editor {
+                Editor::Bool(value) => { put(at, if *value { 'y' } else { 'n' }); }
+                Editor::Range { value, symbols, .. } => { put(at, symbols.symbol(*value).unwrap_or(' ')); }
+                Editor::Choice { value, options } => {
+                    for ch in options.get(*value as usize).copied().unwrap_or("").chars() { put(at, ch); at += 1; }
+                }
+                Editor::Action | Editor::Submenu(_) => {}
+            }
+        }
+        let span = menu.items[menu.selected].span();
+        for cell in 0..BUTD {
+            let ch = self.skinned(cells[cell]);
+            self.set_cell(cell, Cell::from_char(ch));
+            self.set_led(cell, span.contains(&(first + cell)));
+        }
+    }
+}
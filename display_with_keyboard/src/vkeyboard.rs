@@ -0,0 +1,63 @@
+use display::SegmentDisplay;
+use keyboard::{Key, KeySource};
+use crate::{Alphabet, Cell, DisplayAndKeyboard};
+
+/// On-screen keyboard over any alphabet.
+///
+/// The alphabet is laid out one symbol per cell, a display-width page at a
+/// time, and the LED marks the symbol under the cursor. `<-`/`->` move the
+/// cursor and turn the page at its edges, `v`/`^` jump a page forward and
+/// back, `Ent` or `0` pick the symbol, `Esc` or `F1` give up. The cursor
+/// stays where it was between runs.
+pub struct VirtualKeyboard {
+    alphabet: Alphabet,
+    index: usize,
+}
+
+impl VirtualKeyboard {
+    pub fn new(alphabet: Alphabet) -> Self { Self { alphabet, index: 0 } }
+
+    pub fn alphabet(&self) -> Alphabet { return self.alphabet; }
+
+    /// Position of the cursor in the alphabet.
+    pub fn index(&self) -> usize { return self.index; }
+
+    /// Applies one key; `Some` once the run is over, holding the pick.
+    pub fn handle(&mut self, key: Key, page: usize) -> Option<Option<char>> {
+        let count = self.alphabet.len() as usize;
+        match key {
+            Key::Left if self.index > 0 => { self.index -= 1; }
+            Key::Right if self.index + 1 < count => { self.index += 1; }
+            Key::Down => { self.index = (self.index + page).min(count.saturating_sub(1)); }
+            Key::Up => { self.index = self.index.saturating_sub(page); }
+            Key::Ent | Key::Digit(0) => { return Some(self.alphabet.symbol(self.alphabet.first() + self.index as u8)); }
+            Key::Esc | Key::F1 => { return Some(None); }
+            _ => {}
+        }
+        return None;
+    }
+}
+
+impl<const BUTD: usize, D: SegmentDisplay, K: KeySource> DisplayAndKeyboard<BUTD, D, K> {
+    /// Runs `keyboard` over the whole display, see `screen`, and returns
+    /// the picked symbol.
+    pub fn virtual_keyboard(&mut self, keyboard: &mut VirtualKeyboard) -> Option<char> {
+        let saved = self.screen();
+        let res = loop {
+            self.draw_keyboard(keyboard);
+            let key = self.get_pressed();
+            if let Some(pick) = keyboard.handle(key, BUTD) { break pick; }
+        };
+        self.restore(&saved);
+        return res;
+    }
+
+    fn draw_keyboard(&mut self, keyboard: &VirtualKeyboard) {
+        let first = keyboard.index - keyboard.index % BUTD;
+        for cell in 0..BUTD {
+            let symbol = keyboard.alphabet.symbol(keyboard.alphabet.first() + (first + cell) as u8).unwrap_or(' ');
+            self.set_cell(cell, Cell::from_char(symbol));
+            self.set_led(cell, first + cell == keyboard.index);
+        }
+    }
+}
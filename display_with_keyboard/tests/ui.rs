@@ -0,0 +1,245 @@
+//! The widgets driven by a `MockKeyboard`, drawn on a `RecordingDisplay`.
+
+use display_with_keyboard::{DisplayAndKeyboard, Editor, EntryField, Item, Key, Menu, MenuEvent, MockKeyboard, Outcome, RecordingDisplay, Step, Alphabet, DECIMAL, GAME, LETTERS};
+use embassy_time::Duration;
+
+const BUTD: usize = 16;
+
+type Board<'a> = DisplayAndKeyboard<BUTD, RecordingDisplay<BUTD>, MockKeyboard<'a>>;
+
+fn board(keys: &[Key]) -> Board<'_> {
+    return DisplayAndKeyboard::new(RecordingDisplay::new(), MockKeyboard::keys(keys));
+}
+
+fn lit(board: &Board) -> Vec<usize> {
+    return (0..BUTD).filter(|i| board.display().led(*i)).collect();
+}
+
+const START: u8 = 0;
+const CHALLENGE: u8 = 1;
+const SETTINGS: u8 = 2;
+
+fn start_items<'a>() -> [Item<'a>; 3] {
+    return [
+        Item::new(START, "start", 0, Editor::Action),
+        Item::new(CHALLENGE, "seed", 6, Editor::Action),
+        Item::new(SETTINGS, "settings", 11, Editor::Action),
+    ];
+}
+
+#[test]
+fn start_menu_moves_the_cursor_and_chooses() {
+    let keys = [Key::Right, Key::Right, Key::Ent];
+    let mut board = board(&keys);
+    let mut items = start_items();
+    let mut menu = Menu::new(&mut items);
+    assert!(board.menu(&mut menu) == MenuEvent::Action(SETTINGS));
+    // `settings` runs past the last cell, so the menu scrolled by 3.
+    assert_eq!(lit(&board), (8..16).collect::<Vec<_>>());
+    assert!(board.display().shows(8, 's') && board.display().shows(15, 's'));
+    assert!(board.display().shows(0, 'r') && board.display().shows(3, 's'));
+}
+
+#[test]
+fn start_menu_digit_chooses_that_item() {
+    let keys = [Key::Digit(2)];
+    let mut board = board(&keys);
+    let mut items = start_items();
+    let mut menu = Menu::new(&mut items);
+    assert!(board.menu(&mut menu) == MenuEvent::Action(CHALLENGE));
+    assert_eq!(lit(&board), (0..5).collect::<Vec<_>>());
+    assert!(board.display().shows(0, 's') && board.display().shows(6, 's'));
+}
+
+#[test]
+fn start_menu_esc_goes_back() {
+    let keys = [Key::Left, Key::Esc];
+    let mut board = board(&keys);
+    let mut items = start_items();
+    let mut menu = Menu::new(&mut items);
+    assert!(board.menu(&mut menu) == MenuEvent::Back);
+    assert_eq!(menu.selected(), START);
+}
+
+const FLAG: u8 = 3;
+const LEVEL: u8 = 4;
+const MODE: u8 = 5;
+const LAST: u8 = 6;
+const MODES: [&str; 2] = ["classic", "simon"];
+
+#[test]
+fn settings_edit_each_kind_of_item() {
+    let keys = [Key::Ent, Key::Right, Key::Down, Key::Right, Key::Ent, Key::Up];
+    let mut board = board(&keys);
+    let mut items = [
+        Item::new(FLAG, "f", 0, Editor::Bool(false)),
+        Item::new(LEVEL, "d", 3, Editor::Range { value: 1, min: 1, max: 16, symbols: GAME }),
+        Item::new(MODE, "", 6, Editor::Choice { value: 0, options: &MODES }),
+        Item::new(LAST, "a", 30, Editor::Bool(true)),
+    ];
+    let mut menu = Menu::new(&mut items);
+    assert!(board.menu(&mut menu) == MenuEvent::Changed(FLAG));
+    assert_eq!(menu.flag(FLAG), Some(true));
+    // A range wraps around at its ends.
+    assert!(board.menu(&mut menu) == MenuEvent::Changed(LEVEL));
+    assert_eq!(menu.value(LEVEL), Some(16));
+    assert!(board.menu(&mut menu) == MenuEvent::Changed(MODE));
+    assert_eq!(menu.value(MODE), Some(1));
+    assert!(board.menu(&mut menu) == MenuEvent::Changed(MODE));
+    assert_eq!(menu.value(MODE), Some(0));
+    assert!(board.display().shows(1, 'y') && board.display().shows(4, 'g'));
+    assert!(board.display().shows(6, 's'));
+    assert_eq!(lit(&board), (6..13).collect::<Vec<_>>());
+}
+
+#[test]
+fn settings_scroll_to_items_past_the_display() {
+    let keys = [Key::Digit(4), Key::Up];
+    let mut board = board(&keys);
+    let mut items = [
+        Item::new(FLAG, "f", 0, Editor::Bool(false)),
+        Item::new(LEVEL, "d", 3, Editor::Range { value: 1, min: 1, max: 16, symbols: GAME }),
+        Item::new(MODE, "", 6, Editor::Choice { value: 0, options: &MODES }),
+        Item::new(LAST, "a", 30, Editor::Bool(true)),
+    ];
+    let mut menu = Menu::new(&mut items);
+    assert!(board.menu(&mut menu) == MenuEvent::Changed(LAST));
+    assert_eq!(menu.flag(LAST), Some(false));
+    assert!(board.menu(&mut menu) == MenuEvent::Changed(LAST));
+    assert_eq!(menu.flag(LAST), Some(true));
+    // Cells 16..32 are in view, with `an` in the last two.
+    assert!(board.display().shows(14, 'a') && board.display().shows(15, 'n'));
+    assert_eq!(lit(&board), vec![14, 15]);
+    assert!(board.cells()[..14].iter().all(|cell| cell.is_empty()));
+}
+
+const MORE: u8 = 7;
+
+#[test]
+fn submenu_edits_and_goes_back_to_its_parent() {
+    let keys = [Key::Right, Key::Ent, Key::Up, Key::Right, Key::Up, Key::Esc, Key::Esc];
+    let mut board = board(&keys);
+    let mut inner = [
+        Item::new(FLAG, "f", 0, Editor::Bool(false)),
+        Item::new(LEVEL, "d", 3, Editor::Range { value: 1, min: 1, max: 16, symbols: GAME }),
+    ];
+    let mut items = [
+        Item::new(START, "start", 0, Editor::Action),
+        Item::new(MORE, "more", 6, Editor::Submenu(Menu::new(&mut inner))),
+    ];
+    let mut menu = Menu::new(&mut items);
+    assert!(board.menu(&mut menu) == MenuEvent::Changed(FLAG));
+    // The submenu took over the display.
+    assert!(board.display().shows(0, 'f') && board.display().shows(1, 'n'));
+    assert!(board.menu(&mut menu) == MenuEvent::Changed(LEVEL));
+    // `Esc` leaves the submenu and a second one leaves the top level.
+    assert!(board.menu(&mut menu) == MenuEvent::Back);
+    assert_eq!(menu.selected(), MORE);
+    assert!(board.display().shows(6, 'm'));
+    assert_eq!(lit(&board), (6..10).collect::<Vec<_>>());
+    // Nested items are found from the top.
+    assert_eq!(menu.flag(FLAG), Some(true));
+    assert_eq!(menu.value(LEVEL), Some(2));
+    assert_eq!(menu.item(LEVEL).map(|item| item.label), Some("d"));
+}
+
+#[test]
+fn entry_edits_and_submits() {
+    let keys = [Key::Digit(1), Key::Digit(2), Key::Star, Key::Digit(3), Key::Left, Key::Up, Key::Ent];
+    let mut board = board(&keys);
+    let mut field: EntryField<4> = EntryField::new(DECIMAL).min(1);
+    let res = board.entry(&mut field);
+    assert!(res.outcome == Outcome::Submitted);
+    assert_eq!(res.value.as_slice(), &[1, 4]);
+    assert_eq!(res.value.to_number(10), 14);
+    // Right-aligned in the four cells of the field; the cursor LEDs are off.
+    assert!(board.cell(0).is_empty() && board.cell(1).is_empty());
+    assert_eq!(board.cell(2).digit(), Some(1));
+    assert_eq!(board.cell(3).digit(), Some(4));
+    assert!(board.display().shows(2, '1') && board.display().shows(3, '4'));
+    assert!(lit(&board).is_empty());
+}
+
+#[test]
+fn entry_keeps_rolled_over_keys() {
+    // `2` goes down before `1` comes up.
+    let steps = [
+        Step::press(Key::Digit(1)),
+        Step::chord(&[Key::Digit(1), Key::Digit(2)]),
+        Step::press(Key::Digit(2)),
+        Step::release(),
+        Step::press(Key::Ent),
+    ];
+    let mut board: Board = DisplayAndKeyboard::new(RecordingDisplay::new(), MockKeyboard::steps(&steps));
+    let mut field: EntryField<4> = EntryField::new(DECIMAL);
+    let res = board.entry(&mut field);
+    assert!(res.outcome == Outcome::Submitted);
+    assert_eq!(res.value.as_slice(), &[1, 2]);
+}
+
+#[test]
+fn entry_needs_min_symbols_and_can_be_cancelled() {
+    let keys = [Key::Ent, Key::Digit(7), Key::Esc];
+    let mut board = board(&keys);
+    let mut field: EntryField<4> = EntryField::new(DECIMAL).min(2);
+    let res = board.entry(&mut field);
+    assert!(res.outcome == Outcome::Cancelled);
+    assert_eq!(res.value.as_slice(), &[7]);
+}
+
+#[test]
+fn entry_spells_letters_with_zero() {
+    let keys = [Key::Digit(0), Key::Digit(3), Key::Digit(1), Key::Ent];
+    let mut board = board(&keys);
+    let mut field: EntryField<4> = EntryField::new(LETTERS);
+    let res = board.entry(&mut field);
+    // `1` alone is not a letter, only `0 3` is.
+    assert_eq!(res.value.as_slice(), &[3]);
+    assert!(board.display().shows(3, 'c'));
+}
+
+#[test]
+fn entry_picks_from_the_virtual_keyboard() {
+    const ODD: Alphabet = Alphabet::new("13579", 0);
+    let keys = [Key::F2, Key::Right, Key::Right, Key::Ent, Key::Ent];
+    let mut board = board(&keys);
+    let mut field: EntryField<2> = EntryField::new(ODD).at(5);
+    let res = board.entry(&mut field);
+    assert_eq!(res.value.as_slice(), &[2]);
+    assert!(board.display().shows(6, '5'));
+    // The keyboard's page was put away again.
+    assert!(board.cell(0).is_empty());
+}
+
+#[test]
+fn quit_dialog_chooses_and_restores_the_screen() {
+    let keys = [Key::Left, Key::Ent];
+    let mut board = board(&keys);
+    board.print(0, "42");
+    board.set_led(9, true);
+    assert_eq!(board.confirm("quit", &["yes", "no"], 1, None), 0);
+    assert!(board.display().shows(1, '4') && board.display().shows(2, '2'));
+    assert!(board.display().shows(5, ' '));
+    assert_eq!(lit(&board), vec![9]);
+}
+
+#[test]
+fn quit_dialog_digit_and_esc() {
+    let keys = [Key::Digit(2)];
+    assert_eq!(board(&keys).confirm("quit", &["yes", "no"], 0, None), 1);
+    let keys = [Key::Right, Key::Esc];
+    assert_eq!(board(&keys).confirm("quit", &["yes", "no"], 0, None), 0);
+}
+
+#[test]
+fn quit_dialog_times_out_to_the_default() {
+    let mut board = board(&[]);
+    assert_eq!(board.confirm("quit", &["yes", "no"], 1, Some(Duration::from_millis(20))), 1);
+}
+
+#[test]
+fn quit_dialog_default_past_the_options_is_the_last() {
+    let keys = [Key::Esc];
+    assert_eq!(board(&keys).confirm("quit", &["yes", "no"], 5, None), 1);
+    assert_eq!(board(&[]).confirm("quit", &["yes", "no"], 5, Some(Duration::from_millis(20))), 1);
+}
@@ -0,0 +1,693 @@
+#![no_std]
+#![cfg_attr(not(test), no_main)]
+// Every function ends in an explicit `return`, as in the rest of the firmware.
+#![allow(clippy::needless_return)]
+
+//! The memory game itself: menus, rounds and high scores, run as a state
+//! machine over a `SegmentDisplay`, a `KeySource` and a `Storage`. The
+//! firmware plays it on the board; on the host, `RecordingDisplay`,
+//! `MockKeyboard` and `RamFlash` stand in for the hardware.
+
+use core::ops::Range;
+use rand::RngCore;
+use embassy_time::{Duration, Instant, Timer};
+
+use memory_core::{HighScore, Mode, Recall, Rng, Rounds, Rules, Scoring, Sequence, Settings, Storage, Store, Verdict, MAX_BRIGHTNESS, MAX_DIFFICULTY, MIN_DIFFICULTY};
+use display_with_keyboard::{Alphabet, Cell, DisplayAndKeyboard, Editor, Entry, EntryField, EntryResult, Item, KeySource, Menu, MenuEvent, Outcome, SegmentDisplay, DECIMAL, GAME, LETTERS};
+
+// Menu item ids.
+const START: u8 = 0;
+const SETTINGS: u8 = 1;
+const BACK: u8 = 2;
+const B_SKIN: u8 = 3;
+const D_SKIN: u8 = 4;
+const DIFFICULTY: u8 = 5;
+const BRIGHTNESS: u8 = 6;
+const FIXED: u8 = 7;
+const CHALLENGE: u8 = 8;
+const MODE: u8 = 9;
+const RECALL: u8 = 10;
+const TIMED: u8 = 11;
+const ADAPTIVE: u8 = 12;
+
+/// Longest challenge seed, leaving room for the `seed` label.
+const SEED_DIGITS: usize = 11;
+
+/// Names of the `Mode`s, by index.
+const MODE_NAMES: [&str; Mode::COUNT as usize] = ["classic", "simon"];
+/// Names of the `Recall`s, by index.
+const RECALL_NAMES: [&str; Recall::COUNT as usize] = ["forward", "reverse", "sorted", "plus 1", "place"];
+
+/// LEDs the time left of a timed answer is shown on.
+const TIME_BAR: usize = 8;
+/// LEDs a timed answer always leaves for the time left; Simon chains stop
+/// growing short of the field reaching them.
+const MIN_TIME_BAR: usize = 4;
+
+/// Lines of the high-score table.
+const HIGH_SCORES: usize = 5;
+
+/// Brightness 0..=7 is shown as 1 to 8.
+const BRIGHTNESS_SYMBOLS: Alphabet = Alphabet::new("12345678", 0);
+
+/// Where the game is. `Game::step` runs one state and returns the next, so
+/// each state can be driven and tested on its own.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum State<const BUTD: usize> {
+    /// Loading animation after power-up.
+    Boot,
+    /// Start menu.
+    Title,
+    Settings,
+    /// The player types the seed of a challenge.
+    Challenge,
+    /// 3-2-1-GO before a round.
+    Countdown,
+    /// The sequence is shown.
+    Showing,
+    /// The player types the sequence; `typed` is what they have so far and
+    /// `deadline` when their time runs out, if the round is timed.
+    Answering { showed: Sequence, typed: Entry<BUTD>, deadline: Option<Instant> },
+    Correct,
+    GameOver,
+    /// The player cancelled the answer and is asked whether to quit; the
+    /// clock of a timed round keeps running.
+    Paused { showed: Sequence, typed: Entry<BUTD>, deadline: Option<Instant> },
+}
+
+pub struct Game<const BUTD: usize, D: SegmentDisplay, K: KeySource, S: Storage> {
+    board: DisplayAndKeyboard<BUTD, D, K>,
+    rules: Rules,
+    brightness: u8,
+    rng: Rng,
+    /// Seed of the challenge being played, if any.
+    challenge: Option<u64>,
+    /// Score, Simon chain and adaptive level; the level starts at the set
+    /// difficulty and carries over from game to game.
+    rounds: Rounds,
+    /// When the current answer began, moved on by any time spent paused.
+    answer_started: Instant,
+    store: Store<S, HIGH_SCORES>,
+    game_over_hook: Option<fn(&mut K)>,
+    new_game_hook: Option<fn(&mut K, u64)>,
+}
+
+impl<const BUTD: usize, D: SegmentDisplay, K: KeySource, S: Storage> Game<BUTD, D, K, S> {
+    /// `seed` starts the sequence RNG, which the times of key presses then
+    /// keep stirring. Every game starts the RNG over from a seed drawn from
+    /// it, see `on_new_game`; that seed and the key input from the start of
+    /// the game replay the same game. Timed and adaptive rounds also depend on how long answers take, so
+    /// they need `Replay::timed`. Settings and high scores are kept in
+    /// `storage`.
+    pub fn new(display: D, keyboard: K, seed: u64, storage: S) -> Game<BUTD, D, K, S> {
+        let b = DisplayAndKeyboard::new(display, keyboard);
+        let settings = Settings::default();
+        Self { board: b, rules: settings.rules, brightness: settings.brightness, rng: Rng::new(seed), challenge: None, rounds: Rounds::new(settings.rules.difficulty), answer_started: Instant::now(), store: Store::new(storage), game_over_hook: None, new_game_hook: None }
+    }
+
+    /// Calls `hook` with the key source whenever a game is lost.
+    pub fn on_game_over(mut self, hook: fn(&mut K)) -> Self { self.game_over_hook = Some(hook); self }
+
+    /// Calls `hook` with the key source and the seed of the RNG whenever a
+    /// game starts, e.g. to restart a `Recorder`.
+    pub fn on_new_game(mut self, hook: fn(&mut K, u64)) -> Self { self.new_game_hook = Some(hook); self }
+
+    pub async fn run(&mut self) -> ! {
+        let mut state = State::Boot;
+        loop {
+            state = self.step(state).await;
+        }
+    }
+
+    /// Runs `state` and returns the one to go to next.
+    pub async fn step(&mut self, state: State<BUTD>) -> State<BUTD> {
+        match state {
+            State::Boot => {
+                if self.store.load().is_err() { defmt::warn!("saved settings and scores could not be read"); }
+                self.apply(self.store.settings());
+                self.loading().await;
+                return State::Title;
+            }
+            State::Title => {
+                self.start();
+                match self.start_menu() {
+                    SETTINGS => { return State::Settings; }
+                    CHALLENGE => { return State::Challenge; }
+                    _ => {}
+                }
+                self.challenge = None;
+                self.new_game();
+                return State::Countdown;
+            }
+            State::Settings => {
+                self.settings();
+                return State::Title;
+            }
+            State::Challenge => {
+                let Some(seed) = self.ask_seed() else { return State::Title; };
+                self.challenge = Some(seed);
+                self.new_game();
+                self.show_seed(seed).await;
+                return State::Countdown;
+            }
+            State::Countdown => {
+                if self.rules.adaptive { self.show_level().await; }
+                self.round_start().await;
+                return State::Showing;
+            }
+            State::Showing => {
+                let showed = self.show_digits().await;
+                self.answer_started = Instant::now();
+                let deadline = match self.rules.timed {
+                    true => Some(self.answer_started + Duration::from_millis(self.round_rules().answer_ms(showed.len()))),
+                    false => None,
+                };
+                return State::Answering { showed, typed: Entry::new(), deadline };
+            }
+            State::Answering { showed, typed, deadline } => {
+                let answer = self.answer(typed, showed.len(), deadline);
+                if let Some((res, _)) = answer {
+                    if res.outcome == Outcome::Cancelled { return State::Paused { showed, typed: res.value, deadline }; }
+                }
+                let latency = self.answer_started.elapsed().as_millis();
+                let typed = answer.as_ref().map(|(res, _)| res.value.as_slice());
+                let left = answer.and_then(|(_, left)| left).map(|left| left.as_millis());
+                match self.rounds.resolve(&self.rules, &showed, typed, left, latency) {
+                    Verdict::Correct => { return State::Correct; }
+                    Verdict::Wrong | Verdict::TimedOut => { return State::GameOver; }
+                }
+            }
+            State::Paused { showed, typed, deadline } => {
+                let paused = Instant::now();
+                let timeout = deadline.map(|deadline| deadline.saturating_duration_since(paused));
+                if self.board.confirm("quit", &["yes", "no"], 1, timeout) == 0 { return State::Title; }
+                // An untimed answer is not slower for the time in the dialog.
+                if deadline.is_none() { self.answer_started += paused.elapsed(); }
+                return State::Answering { showed, typed, deadline };
+            }
+            State::Correct => {
+                self.right_answer().await;
+                return State::Countdown;
+            }
+            State::GameOver => {
+                if let Some(hook) = self.game_over_hook { hook(self.board.keyboard()); }
+                self.game_over().await;
+                if let Some(seed) = self.challenge { self.show_seed(seed).await; }
+                let points = self.rounds.score().points().min(u32::MAX as u64) as u32;
+                let scoring = Scoring::of(&self.rules);
+                if self.store.high_scores(scoring).qualifies(points) {
+                    let initials = self.ask_initials();
+                    if self.store.add_score(HighScore::new(scoring, points, initials)).is_err() {
+                        defmt::warn!("high score could not be saved");
+                    }
+                    self.show_high_scores(scoring).await;
+                }
+                return State::Title;
+            }
+        }
+    }
+
+    async fn loading(&mut self) {
+        self.board.clean_display();
+        self.board.turn_on_display(self.brightness);
+        self.board.print_char(0, '-');
+        self.board.print_char(1, 'B');
+        Timer::after(Duration::from_millis(100)).await;
+        for count in 0..16 {
+            self.board.print_char((count - 1) * 2, ' ');
+            self.board.print_char((count - 1) * 2+1, ' ');
+            self.board.print_char(count*2, '-');
+            self.board.print_char(count*2, 'B');
+            Timer::after(Duration::from_millis(100)).await;
+        }
+    }
+
+    fn start(&mut self) {
+        self.board.turn_on_display(self.brightness);
+        self.board.clean_display();
+    }
+
+    /// Id of the chosen item.
+    fn start_menu(&mut self) -> u8 {
+        let mut items = [
+            Item::new(START, "start", 0, Editor::Action),
+            Item::new(CHALLENGE, "seed", 6, Editor::Action),
+            Item::new(SETTINGS, "settings", 11, Editor::Action),
+        ];
+        let mut menu = Menu::new(&mut items);
+        loop {
+            let event = self.board.menu(&mut menu);
+            self.stir();
+            if let MenuEvent::Action(id) = event { return id; }
+        }
+    }
+
+    fn settings(&mut self) {
+        let mut items = [
+            Item::new(BACK, "back", 0, Editor::Action),
+            Item::new(B_SKIN, "b", 5, Editor::Bool(self.board.b_skin())),
+            Item::new(D_SKIN, "d", 8, Editor::Bool(self.board.d_skin())),
+            Item::new(DIFFICULTY, "d", 11, Editor::Range { value: self.rules.difficulty, min: MIN_DIFFICULTY, max: MAX_DIFFICULTY, symbols: GAME }),
+            Item::new(BRIGHTNESS, "b", 14, Editor::Range { value: self.brightness, min: 0, max: MAX_BRIGHTNESS, symbols: BRIGHTNESS_SYMBOLS }),
+            Item::new(FIXED, "f", 17, Editor::Bool(self.rules.fixed)),
+            Item::new(MODE, "", 20, Editor::Choice { value: self.rules.mode.index(), options: &MODE_NAMES }),
+            Item::new(RECALL, "", 28, Editor::Choice { value: self.rules.recall.index(), options: &RECALL_NAMES }),
+            Item::new(TIMED, "t", 36, Editor::Bool(self.rules.timed)),
+            Item::new(ADAPTIVE, "a", 39, Editor::Bool(self.rules.adaptive)),
+        ];
+        let mut menu = Menu::new(&mut items);
+        self.board.clean_display();
+        loop {
+            let event = self.board.menu(&mut menu);
+            self.stir();
+            match event {
+                MenuEvent::Back | MenuEvent::Action(BACK) => {
+                    if self.store.save_settings(self.current_settings()).is_err() { defmt::warn!("settings could not be saved"); }
+                    break;
+                }
+                MenuEvent::Changed(B_SKIN) => { if menu.flag(B_SKIN) == Some(!self.board.b_skin()) { self.board.swap_b_skin(); } }
+                MenuEvent::Changed(D_SKIN) => { if menu.flag(D_SKIN) == Some(!self.board.d_skin()) { self.board.swap_d_skin(); } }
+                MenuEvent::Changed(DIFFICULTY) => {
+                    self.rules.difficulty = menu.value(DIFFICULTY).unwrap_or(self.rules.difficulty);
+                    self.rounds.restart_level(self.rules.difficulty);
+                }
+                MenuEvent::Changed(BRIGHTNESS) => {
+                    self.brightness = menu.value(BRIGHTNESS).unwrap_or(self.brightness);
+                    self.board.turn_on_display(self.brightness);
+                }
+                MenuEvent::Changed(FIXED) => { self.rules.fixed = menu.flag(FIXED).unwrap_or(self.rules.fixed); }
+                MenuEvent::Changed(MODE) => { self.rules.mode = menu.value(MODE).and_then(Mode::from_index).unwrap_or(self.rules.mode); }
+                MenuEvent::Changed(RECALL) => { self.rules.recall = menu.value(RECALL).and_then(Recall::from_index).unwrap_or(self.rules.recall); }
+                MenuEvent::Changed(TIMED) => { self.rules.timed = menu.flag(TIMED).unwrap_or(self.rules.timed); }
+                MenuEvent::Changed(ADAPTIVE) => {
+                    self.rules.adaptive = menu.flag(ADAPTIVE).unwrap_or(self.rules.adaptive);
+                    self.rounds.restart_level(self.rules.difficulty);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn current_settings(&self) -> Settings {
+        return Settings { rules: self.rules, brightness: self.brightness, b_skin: self.board.b_skin(), d_skin: self.board.d_skin() };
+    }
+
+    fn apply(&mut self, settings: Settings) {
+        self.rules = settings.rules;
+        self.rounds.restart_level(self.rules.difficulty);
+        self.brightness = settings.brightness;
+        if settings.b_skin != self.board.b_skin() { self.board.swap_b_skin(); }
+        if settings.d_skin != self.board.d_skin() { self.board.swap_d_skin(); }
+    }
+
+    async fn round_start(&mut self) {
+        self.board.clean_display();
+        for count in 0..3 {
+            self.board.print_char(count*6,(b'3' - count as u8) as char);
+            self.board.print_char(30-count*6, (b'3' - count as u8) as char);
+            for c in 0..8 {
+                self.board.print_char(c*2+1, 'B');
+                self.board.print_char(31-c*2, 'B');
+                Timer::after(Duration::from_millis(100)).await;
+            }
+            self.board.clean_display();
+        }
+        self.board.print(14,"GO");
+        let name = match self.rules.recall {
+            Recall::Forward => MODE_NAMES[self.rules.mode.index() as usize],
+            recall => RECALL_NAMES[recall.index() as usize],
+        };
+        self.label(name);
+        self.lights();
+        Timer::after(Duration::from_millis(1000)).await;
+        self.board.clean_display();
+        Timer::after(Duration::from_millis(500)).await;
+    }
+
+
+    /// Rules of the coming round, see `Rounds::rules`.
+    fn round_rules(&self) -> Rules { return self.rounds.rules(&self.rules); }
+
+    /// Shows the level adaptive rounds are at.
+    async fn show_level(&mut self) {
+        self.board.clean_display();
+        self.label("level");
+        self.number(self.rounds.level() as u64, BUTD - 2..BUTD);
+        Timer::after(Duration::from_millis(1000)).await;
+    }
+
+    async fn show_digits(&mut self) -> Sequence {
+        let rules = self.round_rules();
+        let sequence = self.next_sequence();
+        for flash in sequence.flashes() {
+            let cell = flash.cell as usize;
+            if self.rules.recall != Recall::Position {
+                self.board.set_cell(cell, Cell::from_char(GAME.symbol(flash.symbol).unwrap_or(' ')));
+            }
+            self.board.set_led(cell, true);
+            Timer::after(Duration::from_millis(rules.show_ms())).await;
+            self.board.clean_display();
+            Timer::after(Duration::from_millis(rules.gap_ms())).await;
+        }
+        return sequence;
+    }
+
+    /// Sequence of the coming round, see `Rounds::next_sequence`. In a
+    /// timed game, Simon chains stop short of leaving fewer than
+    /// `MIN_TIME_BAR` LEDs.
+    fn next_sequence(&mut self) -> Sequence {
+        let longest = if self.rules.timed { BUTD - MIN_TIME_BAR } else { BUTD };
+        return self.rounds.next_sequence(&self.rules, self.challenge, &mut self.rng, longest);
+    }
+
+    /// Starts a game; a challenge also starts at the set difficulty, so that
+    /// every board on the seed plays the same levels.
+    fn new_game(&mut self) {
+        let seed = self.rng.next_u64();
+        self.rng = Rng::new(seed);
+        if let Some(hook) = self.new_game_hook { hook(self.board.keyboard(), seed); }
+        self.rounds.new_game();
+        if self.challenge.is_some() { self.rounds.restart_level(self.rules.difficulty); }
+    }
+
+    /// Asks for the seed of a challenge; `None` if the player backs out.
+    fn ask_seed(&mut self) -> Option<u64> {
+        self.board.clean_display();
+        self.label("seed");
+        let mut field: EntryField<BUTD> = EntryField::new(DECIMAL).max(SEED_DIGITS).min(1).at(BUTD - SEED_DIGITS);
+        let res = self.board.entry(&mut field);
+        self.stir();
+        if res.outcome == Outcome::Cancelled { return None; }
+        return Some(res.value.to_number(10));
+    }
+
+    /// Shows `seed` so that players can check they are on the same challenge.
+    async fn show_seed(&mut self, seed: u64) {
+        self.board.turn_on_display(self.brightness);
+        self.board.clean_display();
+        self.label("seed");
+        self.number(seed, BUTD - SEED_DIGITS..BUTD);
+        Timer::after(Duration::from_millis(2000)).await;
+        self.board.clean_display();
+    }
+
+    /// Asks a player who made the table for their initials; any left out
+    /// are blanks.
+    fn ask_initials(&mut self) -> [u8; 3] {
+        self.board.turn_on_display(self.brightness);
+        self.board.clean_display();
+        self.label("best");
+        let mut field: EntryField<3> = EntryField::new(LETTERS).at(BUTD - 3);
+        let res = self.board.entry(&mut field);
+        self.stir();
+        let mut initials = [b' '; 3];
+        if res.outcome == Outcome::Submitted {
+            for (i, value) in res.value.as_slice().iter().enumerate() {
+                initials[i] = LETTERS.symbol(*value).unwrap_or(' ') as u8;
+            }
+        }
+        return initials;
+    }
+
+    /// Shows the table of `scoring` a line at a time: rank, initials, score.
+    async fn show_high_scores(&mut self, scoring: Scoring) {
+        for rank in 0..self.store.high_scores(scoring).len() {
+            let entry = self.store.high_scores(scoring).entries()[rank];
+            self.board.clean_display();
+            self.board.set_cell(0, Cell::Digit(rank as u8 + 1));
+            for (i, ch) in entry.initials.iter().enumerate() { self.board.set_cell(2 + i, Cell::from_char(*ch as char)); }
+            self.number(entry.score as u64, 6..BUTD);
+            Timer::after(Duration::from_millis(1500)).await;
+        }
+        self.board.clean_display();
+    }
+
+    /// Writes `value` in decimal, right-aligned in `cells`; digits that do
+    /// not fit are cut off at the left.
+    fn number(&mut self, value: u64, cells: Range<usize>) {
+        let mut value = value;
+        let mut cell = cells.end;
+        while cell > cells.start {
+            cell -= 1;
+            self.board.set_cell(cell, Cell::Digit((value % 10) as u8));
+            value /= 10;
+            if value == 0 { break; }
+        }
+    }
+
+    /// Mixes the time of the last key change into the RNG.
+    fn stir(&mut self) {
+        if let Some(at) = self.board.keyboard().last_change() { self.rng.mix(at); }
+    }
+
+    /// Writes `text` from the first cell on.
+    fn label(&mut self, text: &str) {
+        for (cell, ch) in text.chars().enumerate() { self.board.set_cell(cell, Cell::from_char(ch)); }
+    }
+
+    /// Lets the player type the answer, starting from `typed`.
+    ///
+    /// A timed answer runs until `deadline`, counted down on up to
+    /// `TIME_BAR` LEDs left of the field, and never fewer than
+    /// `MIN_TIME_BAR`. `None` if the time ran out; otherwise the time still
+    /// left comes with the result.
+    fn answer(&mut self, typed: Entry<BUTD>, len: usize, deadline: Option<Instant>) -> Option<(EntryResult<BUTD>, Option<Duration>)> {
+        let mut field: EntryField<BUTD> = EntryField::new(GAME).max(len).min(len).at(BUTD - len);
+        for value in typed.as_slice() { field.put(*value); }
+        let res = match deadline {
+            None => (self.board.entry(&mut field), None),
+            Some(deadline) => {
+                let limit = self.round_rules().answer_ms(len).max(1);
+                let bar = (BUTD - len).min(TIME_BAR);
+                let res = self.board.entry_until(&mut field, deadline, |board, left| {
+                    let lit = (bar as u64 * left.as_millis()).div_ceil(limit).min(bar as u64) as usize;
+                    board.set_leds(0..lit, true);
+                    board.set_leds(lit..bar, false);
+                });
+                self.board.set_leds(0..bar, false);
+                (res?, Some(deadline.saturating_duration_since(Instant::now())))
+            }
+        };
+        self.stir();
+        return Some(res);
+    }
+
+    async fn right_answer(&mut self) {
+        self.board.clean_display();
+        self.board.print(0, "SCORE");
+        let score = self.rounds.score().points();
+        if score >= 100 {
+            self.board.print_char(26, (((score / 100) as u8) + b'0') as char);
+        }
+        if score >= 10 {
+            self.board.print_char(28, ((((score % 100) / 10) as u8) + b'0') as char);
+        }
+        self.board.print_char(30, (((score % 10) as u8) + b'0') as char);
+        for count in  0..16 {
+            self.board.print_char( (count%8)*2+1, ' ');
+            self.board.print_char( 31-(count%8)*2, ' ');
+            self.board.print_char( ((count+1)%8)*2+1, 'B');
+            self.board.print_char( 31-((count+1)%8)*2, 'B');
+            Timer::after(Duration::from_millis(100)).await;
+        }
+    }
+
+    async fn game_over(&mut self) {
+        self.board.clean_display();
+        self.board.print(8, "GAMEOVER");
+        self.lights();
+        for count in 0..7 {
+            self.board.turn_on_display(6 - count);
+            Timer::after(Duration::from_millis(400)).await;
+        }
+        self.board.turn_off_display();
+        Timer::after(Duration::from_millis(300)).await;
+    }
+
+    fn lights(&mut self) {
+        for count in 0..16 {
+            self.board.print_char(count * 2 + 1, 'B');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+    use std::vec::Vec;
+    use display_with_keyboard::{Key, MockKeyboard, Recorder, RecordingDisplay};
+    use memory_core::RamFlash;
+    use super::*;
+
+    const BUTD: usize = 16;
+
+    type Flash = RamFlash<1024>;
+
+    type TestGame<'a> = Game<BUTD, RecordingDisplay<BUTD>, MockKeyboard<'a>, Flash>;
+
+    fn game(keys: &[Key]) -> TestGame<'_> {
+        return Game::new(RecordingDisplay::new(), MockKeyboard::keys(keys), 1, Flash::new());
+    }
+
+    /// Polls `future` until it is done; the timers it waits on are checked
+    /// on every poll.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(res) = future.as_mut().poll(&mut cx) { return res; }
+        }
+    }
+
+    /// What a fresh boot loads from the game's storage.
+    fn reload(game: &mut TestGame) -> Store<Flash, HIGH_SCORES> {
+        let mut flash = Flash::new();
+        *flash.bytes_mut() = *game.store.storage().bytes();
+        let mut store = Store::new(flash);
+        store.load().unwrap();
+        return store;
+    }
+
+    fn sequence(rules: &Rules) -> Sequence { return Sequence::generate(rules, &mut Rng::new(3)); }
+
+    /// Values the player has to type for `showed`.
+    fn expected(showed: &Sequence) -> Vec<u8> {
+        return showed.expected(&Rules::default())[..showed.len()].to_vec();
+    }
+
+    /// Keys that type `value` into a `GAME` field; `0` switches to letters.
+    fn keys_for(value: u8) -> Vec<Key> {
+        if value <= 9 { return std::vec![Key::Digit(value)]; }
+        return std::vec![Key::Digit(0), Key::Digit(value - 9)];
+    }
+
+    /// Answering `showed`, timed to run out after `left`.
+    fn answering(showed: Sequence, left: Option<Duration>) -> State<BUTD> {
+        return State::Answering { showed, typed: Entry::new(), deadline: left.map(|left| Instant::now() + left) };
+    }
+
+    #[test]
+    fn title_goes_where_the_start_menu_says() {
+        assert!(block_on(game(&[Key::Ent]).step(State::Title)) == State::Countdown);
+        assert!(block_on(game(&[Key::Digit(2)]).step(State::Title)) == State::Challenge);
+        assert!(block_on(game(&[Key::Right, Key::Right, Key::Ent]).step(State::Title)) == State::Settings);
+    }
+
+    #[test]
+    fn new_game_restarts_the_recorder_from_the_rng_seed() {
+        let recorder: Recorder<_, 16> = Recorder::new(MockKeyboard::keys(&[Key::Ent]), 1);
+        let mut game = Game::<BUTD, RecordingDisplay<BUTD>, _, _>::new(RecordingDisplay::new(), recorder, 1, Flash::new())
+            .on_new_game(|recorder, seed| recorder.restart(seed));
+        assert!(block_on(game.step(State::Title)) == State::Countdown);
+        let recorder = game.board.keyboard();
+        assert_ne!(recorder.seed(), 1);
+        assert_eq!(recorder.events().count(), 0);
+        assert_eq!(game.rng.next_u64(), Rng::new(game.board.keyboard().seed()).next_u64());
+    }
+
+    #[test]
+    fn settings_are_applied_and_saved() {
+        // Difficulty up one, timed on, then back.
+        let keys = [Key::Digit(4), Key::Up, Key::Digit(9), Key::Esc];
+        let mut game = game(&keys);
+        assert!(block_on(game.step(State::Settings)) == State::Title);
+        assert_eq!(game.rules.difficulty, Rules::default().difficulty + 1);
+        assert!(game.rules.timed);
+        assert_eq!(reload(&mut game).settings(), game.current_settings());
+    }
+
+    #[test]
+    fn challenge_starts_at_the_set_difficulty() {
+        let mut game = game(&[]);
+        game.rules.adaptive = true;
+        let showed = game.next_sequence();
+        game.rounds.resolve(&game.rules, &showed, None, None, 0);
+        assert_eq!(game.rounds.level(), game.rules.difficulty - 1);
+        game.challenge = Some(7);
+        game.new_game();
+        assert_eq!(game.rounds.level(), game.rules.difficulty);
+    }
+
+    #[test]
+    fn answer_can_be_paused_and_resumed() {
+        let showed = sequence(&Rules::default());
+        let values = expected(&showed);
+        let mut keys = keys_for(values[0]);
+        // Quit? No.
+        keys.extend([Key::Esc, Key::Ent]);
+        for value in &values[1..] { keys.extend(keys_for(*value)); }
+        keys.push(Key::Ent);
+        let mut game = game(&keys);
+        let state = block_on(game.step(answering(showed, None)));
+        let State::Paused { typed, .. } = state else { panic!("not paused") };
+        assert_eq!(typed.as_slice(), &values[..1]);
+        let state = block_on(game.step(state));
+        let State::Answering { typed, .. } = state else { panic!("not answering") };
+        assert_eq!(typed.as_slice(), &values[..1]);
+        assert!(block_on(game.step(state)) == State::Correct);
+        assert_eq!(game.rounds.score().points(), 1);
+    }
+
+    #[test]
+    fn quitting_from_pause_goes_to_the_title() {
+        let showed = sequence(&Rules::default());
+        let mut game = game(&[Key::Left, Key::Ent]);
+        let state = State::Paused { showed, typed: Entry::new(), deadline: None };
+        assert!(block_on(game.step(state)) == State::Title);
+    }
+
+    #[test]
+    fn timed_answer_runs_out_while_paused() {
+        let showed = sequence(&Rules::default());
+        let values = expected(&showed);
+        // The dialog gives up at the deadline, and the right answer is too late.
+        let mut keys = std::vec![Key::Esc];
+        for value in values { keys.extend(keys_for(value)); }
+        keys.push(Key::Ent);
+        let mut game = game(&keys);
+        let state = block_on(game.step(answering(showed, Some(Duration::from_millis(50)))));
+        let State::Paused { deadline, .. } = state else { panic!("not paused") };
+        while Instant::now() < deadline.unwrap() {}
+        let state = block_on(game.step(state));
+        assert!(state == State::Answering { showed, typed: Entry::new(), deadline });
+        assert!(block_on(game.step(state)) == State::GameOver);
+    }
+
+    #[test]
+    fn wrong_answer_ends_the_game() {
+        let showed = sequence(&Rules::default());
+        let mut keys = Vec::new();
+        for value in expected(&showed) { keys.extend(keys_for(value % 16 + 1)); }
+        keys.push(Key::Ent);
+        let mut game = game(&keys);
+        assert!(block_on(game.step(answering(showed, None))) == State::GameOver);
+        assert_eq!(game.rounds.score().points(), 0);
+    }
+
+    #[test]
+    fn timed_answer_that_runs_out_ends_the_game() {
+        let showed = sequence(&Rules::default());
+        let mut game = game(&[]);
+        assert!(block_on(game.step(answering(showed, Some(Duration::from_millis(30))))) == State::GameOver);
+    }
+
+    #[test]
+    fn high_score_is_saved_with_the_initials() {
+        let keys = [Key::Digit(0), Key::Digit(1), Key::Digit(0), Key::Digit(2), Key::Ent];
+        let mut game = game(&keys);
+        for _ in 0..2 {
+            let showed = game.next_sequence();
+            let values = expected(&showed);
+            game.rounds.resolve(&game.rules, &showed, Some(&values), None, 0);
+        }
+        assert!(block_on(game.step(State::GameOver)) == State::Title);
+        let saved = [HighScore::new(Scoring::Classic, 2, *b"ab ")];
+        assert_eq!(game.store.high_scores(Scoring::Classic).entries(), &saved);
+        assert_eq!(reload(&mut game).high_scores(Scoring::Classic).entries(), &saved);
+        assert!(game.store.high_scores(Scoring::Timed).is_empty());
+    }
+}
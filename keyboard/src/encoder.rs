@@ -0,0 +1,61 @@
+use core::num::NonZeroU8;
+use embassy_stm32::gpio::{AnyPin, Input};
+use embassy_stm32::timer::qei::Qei;
+use embassy_stm32::timer::CaptureCompare16bitInstance;
+use crate::{Key, KeySet, KeySource, Scan};
+
+/// Quadrature rotary encoder with a push switch, read as a key source.
+///
+/// The timer runs in encoder mode, so steps are counted in hardware even
+/// while nobody scans. Each detent clockwise is reported as an `Up` press,
+/// counter-clockwise as `Down`, with a released scan after each one so that
+/// every detent is its own key press. The switch, wired to ground with a
+/// pull-up, is `Ent` while held. Swap the A and B pins to reverse the
+/// direction.
+pub struct RotaryEncoder<'d, T: CaptureCompare16bitInstance> {
+    qei: Qei<'d, T>,
+    button: Input<'d, AnyPin>,
+    counts_per_detent: i16,
+    last: u16,
+    residue: i16,
+    pending: i16,
+    gap: bool,
+}
+
+impl<'d, T: CaptureCompare16bitInstance> RotaryEncoder<'d, T> {
+    /// `counts_per_detent` is 4 for most mechanical encoders.
+    pub fn new(qei: Qei<'d, T>, button: Input<'d, AnyPin>, counts_per_detent: NonZeroU8) -> Self {
+        let last = qei.count();
+        Self { qei, button, counts_per_detent: counts_per_detent.get() as i16, last, residue: 0, pending: 0, gap: false }
+    }
+
+    /// Moves counts from the timer into whole detents waiting to be reported.
+    fn collect(&mut self) {
+        let count = self.qei.count();
+        self.residue += count.wrapping_sub(self.last) as i16;
+        self.last = count;
+        let steps = self.residue / self.counts_per_detent;
+        self.residue -= steps * self.counts_per_detent;
+        self.pending = self.pending.saturating_add(steps);
+    }
+}
+
+impl<'d, T: CaptureCompare16bitInstance> KeySource for RotaryEncoder<'d, T> {
+    fn scan(&mut self) -> Scan {
+        self.collect();
+        let mut keys = KeySet::empty();
+        if self.button.is_low() { keys.insert(Key::Ent); }
+        if self.gap {
+            self.gap = false;
+        } else if self.pending > 0 {
+            keys.insert(Key::Up);
+            self.pending -= 1;
+            self.gap = true;
+        } else if self.pending < 0 {
+            keys.insert(Key::Down);
+            self.pending += 1;
+            self.gap = true;
+        }
+        return Scan { keys, ghosted: false };
+    }
+}
@@ -0,0 +1,159 @@
+use embassy_stm32::exti::ExtiInput;
+use embassy_stm32::gpio::AnyPin;
+use embassy_stm32::i2c::{Error, I2c, Instance};
+use crate::{KeySource, Keymap, MatrixState, Scan};
+
+// MCP23017 registers, IOCON.BANK = 0 so port A and B are consecutive.
+const IODIRA: u8 = 0x00;
+const GPINTENA: u8 = 0x04;
+const IOCON: u8 = 0x0A;
+const GPPUA: u8 = 0x0C;
+const GPIOA: u8 = 0x12;
+const OLATA: u8 = 0x14;
+/// IOCON.MIRROR: INTA and INTB both fire for either port.
+const MIRROR: u8 = 0x40;
+
+/// I2C port expander the keypad hangs off, with its 7-bit address.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Expander {
+    Pcf8574(u8),
+    Mcp23017(u8),
+}
+
+impl Expander {
+    fn address(self) -> u8 {
+        match self {
+            Expander::Pcf8574(address) | Expander::Mcp23017(address) => address,
+        }
+    }
+
+    fn lines(self) -> usize {
+        match self {
+            Expander::Pcf8574(_) => 8,
+            Expander::Mcp23017(_) => 16,
+        }
+    }
+}
+
+/// Why an `I2cKeyboard` could not be set up.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ExpanderError {
+    /// `ROW + COL` is more lines than the expander has.
+    TooManyLines,
+    Bus(Error),
+}
+
+impl From<Error> for ExpanderError {
+    fn from(error: Error) -> Self { return ExpanderError::Bus(error); }
+}
+
+/// Keypad matrix scanned through an I2C port expander.
+///
+/// Rows sit on expander lines `0..ROW` with pull-ups, columns on the lines
+/// right after them. A column is strobed by pulling it low while the others
+/// float, so the wiring is always columns driven, active low. Between scans
+/// all columns are held low: any press then pulls a row down and the
+/// expander raises its interrupt line, so an idle keypad costs no bus
+/// traffic when `int` is given.
+pub struct I2cKeyboard<'d, T: Instance, const ROW: usize, const COL: usize> {
+    i2c: I2c<'d, T>,
+    chip: Expander,
+    keymap: Keymap<ROW, COL>,
+    int: Option<ExtiInput<'d, AnyPin>>,
+    held: bool,
+    error: Option<Error>,
+}
+
+impl<'d, T: Instance, const ROW: usize, const COL: usize> I2cKeyboard<'d, T, ROW, COL> {
+    pub fn new(i2c: I2c<'d, T>, chip: Expander, keymap: Keymap<ROW, COL>, int: Option<ExtiInput<'d, AnyPin>>) -> Result<Self, ExpanderError> {
+        if ROW + COL > chip.lines() { return Err(ExpanderError::TooManyLines); }
+        let mut res = Self { i2c, chip, keymap, int, held: false, error: None };
+        if let Expander::Mcp23017(address) = chip {
+            // IOCON is one register, mirrored at 0x0B; a pair write would
+            // clear MIRROR again.
+            res.i2c.blocking_write(address, &[IOCON, MIRROR])?;
+            res.write_reg(GPPUA, Self::rows())?;
+            res.write_reg(OLATA, 0)?;
+            res.write_reg(GPINTENA, Self::rows())?;
+        }
+        res.drive(Self::cols())?;
+        return Ok(res);
+    }
+
+    fn rows() -> u16 { return (1 << ROW) - 1; }
+
+    fn cols() -> u16 { return ((1 << COL) - 1) << ROW; }
+
+    /// Writes a register pair, port A first.
+    fn write_reg(&mut self, reg: u8, value: u16) -> Result<(), Error> {
+        let [a, b] = value.to_le_bytes();
+        return self.i2c.blocking_write(self.chip.address(), &[reg, a, b]);
+    }
+
+    fn read_reg(&mut self, reg: u8) -> Result<u16, Error> {
+        let mut buf: [u8; 2] = [0; 2];
+        self.i2c.blocking_write_read(self.chip.address(), &[reg], &mut buf)?;
+        return Ok(u16::from_le_bytes(buf));
+    }
+
+    /// Pulls the lines in `cols` low, releases the rest and reads the port
+    /// back. Reading also clears a pending interrupt.
+    fn drive(&mut self, cols: u16) -> Result<u16, Error> {
+        match self.chip {
+            Expander::Pcf8574(address) => {
+                self.i2c.blocking_write(address, &[!cols as u8])?;
+                let mut buf: [u8; 1] = [0];
+                self.i2c.blocking_read(address, &mut buf)?;
+                return Ok(buf[0] as u16);
+            }
+            Expander::Mcp23017(_) => {
+                self.write_reg(IODIRA, !cols)?;
+                return self.read_reg(GPIOA);
+            }
+        }
+    }
+
+    /// Strobes each column in turn and leaves all of them low again.
+    pub fn read_matrix(&mut self) -> Result<MatrixState<ROW, COL>, Error> {
+        let mut state = MatrixState::empty();
+        for col in 0..COL {
+            let port = self.drive(1 << (ROW + col))?;
+            for row in 0..ROW {
+                if port & (1 << row) == 0 { state.set(row, col); }
+            }
+        }
+        self.drive(Self::cols())?;
+        return Ok(state);
+    }
+
+    /// Waits for the expander's interrupt line; returns at once without one.
+    pub async fn wait_for_activity(&mut self) {
+        if let Some(int) = &mut self.int { int.wait_for_low().await; }
+    }
+
+    /// Bus error of the last scan, which then reported no keys.
+    pub fn error(&self) -> Option<Error> { return self.error; }
+}
+
+impl<'d, T: Instance, const ROW: usize, const COL: usize> KeySource for I2cKeyboard<'d, T, ROW, COL> {
+    fn scan(&mut self) -> Scan {
+        if !self.held {
+            if let Some(int) = &self.int {
+                if int.is_high() { return Scan::default(); }
+            }
+        }
+        match self.read_matrix() {
+            Ok(state) => {
+                let scan = state.decode(&self.keymap);
+                self.held = !scan.keys.is_empty();
+                self.error = None;
+                return scan;
+            }
+            Err(error) => {
+                self.held = false;
+                self.error = Some(error);
+                return Scan::default();
+            }
+        }
+    }
+}
@@ -0,0 +1,20 @@
+//! `embassy-time` logs through `defmt`, which needs a logger to link. Host
+//! tests have nowhere to send the output, so it is dropped. Test binaries
+//! of other crates get it with the `host-logger` feature.
+
+#[defmt::global_logger]
+struct Discard;
+
+unsafe impl defmt::Logger for Discard {
+    fn acquire() {}
+    unsafe fn flush() {}
+    unsafe fn release() {}
+    unsafe fn write(_bytes: &[u8]) {}
+}
+
+defmt::timestamp!("");
+
+#[defmt::panic_handler]
+fn defmt_panic() -> ! {
+    panic!("defmt panic");
+}
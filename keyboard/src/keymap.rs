@@ -0,0 +1,169 @@
+/// Logical key, independent of where it sits on the keypad.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, defmt::Format)]
+pub enum Key {
+    /// `0..=9`.
+    Digit(u8),
+    F1,
+    F2,
+    Hash,
+    Star,
+    Up,
+    Down,
+    Left,
+    Right,
+    Esc,
+    Ent,
+    A,
+    B,
+    C,
+    D,
+}
+
+use Key::*;
+
+impl Key {
+    /// Number of distinct keys, digits counted one by one.
+    pub const COUNT: u8 = 24;
+
+    /// Dense index in `0..Key::COUNT`, digits first. Panics for a digit
+    /// above 9, which would take the index of another key.
+    pub const fn index(self) -> u8 {
+        match self {
+            Digit(d) => { assert!(d <= 9, "digit keys are 0..=9"); d }
+            F1 => 10,
+            F2 => 11,
+            Hash => 12,
+            Star => 13,
+            Up => 14,
+            Down => 15,
+            Left => 16,
+            Right => 17,
+            Esc => 18,
+            Ent => 19,
+            A => 20,
+            B => 21,
+            C => 22,
+            D => 23,
+        }
+    }
+
+    pub const fn from_index(index: u8) -> Option<Key> {
+        match index {
+            0..=9 => Some(Digit(index)),
+            10 => Some(F1),
+            11 => Some(F2),
+            12 => Some(Hash),
+            13 => Some(Star),
+            14 => Some(Up),
+            15 => Some(Down),
+            16 => Some(Left),
+            17 => Some(Right),
+            18 => Some(Esc),
+            19 => Some(Ent),
+            20 => Some(A),
+            21 => Some(B),
+            22 => Some(C),
+            23 => Some(D),
+            _ => None,
+        }
+    }
+
+    /// Value of a digit key.
+    pub fn digit(self) -> Option<u8> {
+        match self {
+            Digit(d) => Some(d),
+            _ => None,
+        }
+    }
+}
+
+/// Maps matrix positions `(row, col)` to logical keys. Which side is strobed
+/// is up to the keyboard's `Wiring`, not the keymap.
+///
+/// Custom layouts are plain const data, so a digit out of range fails the
+/// build:
+/// ```ignore
+/// const PAD: Keymap<2, 2> = Keymap::new([[Key::Up, Key::Ent], [Key::Down, Key::Esc]]);
+/// ```
+#[derive(Copy, Clone)]
+pub struct Keymap<const ROW: usize, const COL: usize> {
+    keys: [[Key; COL]; ROW],
+}
+
+impl<const ROW: usize, const COL: usize> Keymap<ROW, COL> {
+    pub const fn new(keys: [[Key; COL]; ROW]) -> Self {
+        let mut row: usize = 0;
+        while row < ROW {
+            let mut col: usize = 0;
+            while col < COL {
+                keys[row][col].index();
+                col += 1;
+            }
+            row += 1;
+        }
+        Self { keys }
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Key { return self.keys[row][col]; }
+
+    /// Matrix position of `key`, if the layout has it.
+    pub fn position(&self, key: Key) -> Option<(usize, usize)> {
+        for row in 0..ROW {
+            for col in 0..COL {
+                if self.keys[row][col] == key { return Some((row, col)); }
+            }
+        }
+        return None;
+    }
+}
+
+/// The 5x4 keypad of the game board.
+pub const KEYPAD_5X4: Keymap<5, 4> = Keymap::new([
+    [F1, F2, Hash, Star],
+    [Digit(1), Digit(2), Digit(3), Up],
+    [Digit(4), Digit(5), Digit(6), Down],
+    [Digit(7), Digit(8), Digit(9), Esc],
+    [Left, Digit(0), Right, Ent],
+]);
+
+/// Common 4x4 membrane keypad.
+pub const MEMBRANE_4X4: Keymap<4, 4> = Keymap::new([
+    [Digit(1), Digit(2), Digit(3), A],
+    [Digit(4), Digit(5), Digit(6), B],
+    [Digit(7), Digit(8), Digit(9), C],
+    [Star, Digit(0), Hash, D],
+]);
+
+/// Common 4x3 membrane keypad.
+pub const MEMBRANE_4X3: Keymap<4, 3> = Keymap::new([
+    [Digit(1), Digit(2), Digit(3)],
+    [Digit(4), Digit(5), Digit(6)],
+    [Digit(7), Digit(8), Digit(9)],
+    [Star, Digit(0), Hash],
+]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_round_trips() {
+        for index in 0..Key::COUNT {
+            assert_eq!(Key::from_index(index).map(Key::index), Some(index));
+        }
+        assert_eq!(Key::from_index(Key::COUNT), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn digit_above_nine_is_rejected() {
+        Digit(10).index();
+    }
+
+    #[test]
+    fn position_finds_keys() {
+        assert_eq!(KEYPAD_5X4.position(Digit(0)), Some((4, 1)));
+        assert_eq!(KEYPAD_5X4.position(Esc), Some((3, 3)));
+        assert_eq!(KEYPAD_5X4.position(A), None);
+    }
+}
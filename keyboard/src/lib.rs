@@ -1,123 +1,177 @@
 #![no_std]
-#![no_main]
+#![cfg_attr(not(test), no_main)]
 #![feature(type_alias_impl_trait)]
 #![feature(adt_const_params)]
 
 use core::ptr::{addr_of_mut, null, null_mut};
+#[cfg(feature = "stm32")]
 use embassy_stm32::{self, gpio::{Level, Output, Speed}, into_ref, Peripheral};
+#[cfg(feature = "stm32")]
 use embassy_stm32::gpio::{Flex, Input, Pin, Pull, AnyPin};
+#[cfg(feature = "stm32")]
 use embassy_stm32::peripherals::{PB7, PB8, PB9};
+#[cfg(feature = "stm32")]
 use embassy_stm32::time::khz;
+use embassy_time::{Duration, Instant};
+#[cfg(feature = "stm32")]
+mod encoder;
+#[cfg(feature = "stm32")]
+mod expander;
+mod keymap;
+mod map;
+mod matrix;
+mod merged;
+mod mock;
+mod record;
+mod scan;
+#[cfg(any(test, feature = "host-logger"))]
+mod host_logger;
+mod wait;
+#[cfg(feature = "stm32")]
+pub use encoder::RotaryEncoder;
+#[cfg(feature = "stm32")]
+pub use expander::{Expander, ExpanderError, I2cKeyboard};
+pub use keymap::{Key, Keymap, KEYPAD_5X4, MEMBRANE_4X3, MEMBRANE_4X4};
+pub use map::{CapacityError, FixedMap};
+pub use matrix::{Drive, MatrixState, Polarity, Wiring, COLUMNS_ACTIVE_HIGH};
+pub use merged::Merged;
+pub use mock::{Hold, MockKeyboard, Step};
+pub use record::{KeyEvent, Recorder, Replay};
+pub use scan::{KeySet, Presses, Scan};
+pub use wait::{wait_key, wait_key_cancellable, wait_press, POLL};
 
+/// Anything that can report the keys held right now: the matrix scanner,
+/// a recorder wrapped around it, a replay of a recorded session, an
+/// `I2cKeyboard` behind a port expander, a `RotaryEncoder` or a scripted
+/// `MockKeyboard`.
+pub trait KeySource {
+    fn scan(&mut self) -> Scan;
 
-struct Map <const SIZE: usize, const DIG: usize> {
-    slots: [usize; 255],
-    values: [bool; SIZE],
-    cur_size: usize
-}
+    /// When the held keys last changed, in milliseconds on the source's own
+    /// clock. A replay reports the times that were recorded, so noise taken
+    /// from here comes out the same again; `None` where no time is kept.
+    fn last_change(&self) -> Option<u64> { return None; }
 
-impl <const SIZE: usize, const DIG: usize>Map <SIZE, DIG> {
-    fn new(s: &str, digits: [char; DIG]) -> Self {
-        let mut i: usize = 0;
-        let mut slots: [usize; 255] = [0; 255];
-        let mut values: [bool; SIZE] = [false; SIZE];
-        for ch in s.chars(){
-            while i<DIG {
-                values[i] = (ch == digits[i]);
-                i += 1;
-            }
+    /// Blocks until keys go down and all come back up, and returns every key
+    /// held in between, for telling chords apart. Ghosting seen in any
+    /// snapshot marks the whole result as ghosted. Typing goes through
+    /// `wait_key` instead, which keeps rolled-over keys apart.
+    fn wait_press(&mut self) -> Scan {
+        let mut scan = self.scan();
+        while scan.keys.is_empty() { scan = self.scan(); }
+        return finish_press(self, scan);
+    }
+
+    /// Like `wait_press`, but gives up if nothing is pressed by `deadline`.
+    /// A press already under way is always waited out.
+    fn wait_press_until(&mut self, deadline: Instant) -> Option<Scan> {
+        let mut scan = self.scan();
+        while scan.keys.is_empty() {
+            if Instant::now() >= deadline { return None; }
+            scan = self.scan();
         }
-        Self {slots, values, cur_size: 40}
+        return Some(finish_press(self, scan));
     }
 
-    fn hash_value(&mut self, character: char) -> usize{
-        return (character as u8) as usize;
+    /// Blocks until a key goes down, see `Presses::next`.
+    fn wait_key(&mut self, presses: &mut Presses) -> Key {
+        loop {
+            if let Some(key) = presses.next(self.scan()) { return key; }
+        }
     }
 
-    fn put(&mut self, key: char, val: bool){
-        let slot = self.hash_value(key);
-        self.slots[slot] = self.cur_size;
-        self.values[self.cur_size] = val;
-        self.cur_size += 1;
+    /// Blocking `wait_key` that gives up after `timeout`.
+    fn wait_key_timeout(&mut self, presses: &mut Presses, timeout: Duration) -> Option<Key> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(key) = presses.next(self.scan()) { return Some(key); }
+            if Instant::now() >= deadline { return None; }
+        }
     }
+}
 
-    fn get(&mut self, character: char) -> bool{
-        let slot = self.hash_value(character);
-        return self.values[self.slots[slot]];
+fn finish_press<S: KeySource + ?Sized>(source: &mut S, mut scan: Scan) -> Scan {
+    let mut res = scan;
+    while !scan.keys.is_empty() {
+        scan = source.scan();
+        res.keys = res.keys.union(scan.keys);
+        res.ghosted |= scan.ghosted;
     }
+    return res;
 }
 
+
+#[cfg(feature = "stm32")]
 pub struct Keyboard <'d, const ROW: usize, const COL: usize>{
-    input: [Input<'d, AnyPin>; ROW],
-    output: [Output<'d, AnyPin>; COL],
+    rows: [Flex<'d, AnyPin>; ROW],
+    cols: [Flex<'d, AnyPin>; COL],
+    keymap: Keymap<ROW, COL>,
+    wiring: Wiring,
 }
-fn init_row<'d> (p: AnyPin) -> Input<'d, AnyPin>{
+#[cfg(feature = "stm32")]
+fn init_line<'d> (p: AnyPin) -> Flex<'d, AnyPin>{
     into_ref!(p);
-    Input::new(p, Pull::Down)
+    Flex::new(p)
 }
 
-fn init_col<'d> (p: AnyPin) -> Output<'d, AnyPin>{
-    into_ref!(p);
-    Output::new(p, Level::Low, Speed::Low)
+#[cfg(feature = "stm32")]
+fn strobe(line: &mut Flex<AnyPin>, active: bool, polarity: Polarity){
+    if active == (polarity == Polarity::ActiveHigh) { line.set_high(); } else { line.set_low(); }
 }
 
-impl <'d, const ROW: usize, const COL: usize> Keyboard<'d, ROW, COL>{
-    pub fn new(mut inputs: [AnyPin; ROW], mut outputs: [AnyPin; COL]) -> Self{
-        Self { input: inputs.map(init_row), output: outputs.map(init_col) }
-    }
+#[cfg(feature = "stm32")]
+fn sense(line: &Flex<AnyPin>, polarity: Polarity) -> bool{
+    return line.is_high() == (polarity == Polarity::ActiveHigh);
+}
 
-    fn read_column(&mut self, column: usize) -> [u8; ROW]{
-        let mut keys: [u8; ROW] = [0; ROW];
-        self.output[column].set_high();
-        let mut i: usize = 0;
-        while i<ROW {
-            if self.input[i].is_high() { keys[i] = 1; }
-            i += 1;
-        }
-        self.output[column].set_low();
-        return keys;
-    }
-    /*
-    [
-    [0 - F1, 1 - F2, 2 - #,  3 - *],
-    [0 - 1,  1 - 2,  2 - 3,  3 - ^],
-    [0 - 4,  1 - 5,  2 - 6,  3 - v],
-    [0 - 7,  1 - 8,  2 - 9,  3 - Esc],
-    [0 - <-, 1 - 0,  2 - ->, 3 - Ent]
-    ]
-     */
-    pub fn read_key(&mut self) -> [[u8; ROW]; COL]{
-        let mut keys: [[u8; ROW]; COL] = [[0; ROW]; COL];
-        let mut i: usize = 0; let mut j: usize = 0;
-        while i<COL {
-            let tmp: [u8; ROW] = self.read_column(i);
-            j = 0;
-            while j<ROW {
-                keys[i][j] = tmp[j];
-                j += 1;
+#[cfg(feature = "stm32")]
+impl <'d, const ROW: usize, const COL: usize> Keyboard<'d, ROW, COL>{
+    /// `rows` and `cols` follow the keymap; `wiring` decides which of them
+    /// are strobed and which are sensed.
+    pub fn new(rows: [AnyPin; ROW], cols: [AnyPin; COL], keymap: Keymap<ROW, COL>, wiring: Wiring) -> Self{
+        let mut res = Self { rows: rows.map(init_line), cols: cols.map(init_line), keymap, wiring };
+        let pull = if wiring.polarity == Polarity::ActiveHigh { Pull::Down } else { Pull::Up };
+        match wiring.drive {
+            Drive::Columns => {
+                for line in res.cols.iter_mut() { strobe(line, false, wiring.polarity); line.set_as_output(Speed::Low); }
+                for line in res.rows.iter_mut() { line.set_as_input(pull); }
+            }
+            Drive::Rows => {
+                for line in res.rows.iter_mut() { strobe(line, false, wiring.polarity); line.set_as_output(Speed::Low); }
+                for line in res.cols.iter_mut() { line.set_as_input(pull); }
             }
-            i+=1;
         }
-        return keys;
+        return res;
     }
 
-    pub fn get_pressed(&mut self, keys: [[u8; ROW]; COL]) -> u8{
-        let mut i: usize = 0; let mut j: usize = 0;
-        while i<COL{
-            j = 0;
-            while j<ROW {
-                if keys[i][j] == 1 { return (i*ROW+j+1) as u8 }
-                j+=1;
+    /// Strobes every driven line in turn and records which keys close it.
+    pub fn read_matrix(&mut self) -> MatrixState<ROW, COL>{
+        let polarity = self.wiring.polarity;
+        let (driven, sensed) = match self.wiring.drive {
+            Drive::Columns => (&mut self.cols[..], &self.rows[..]),
+            Drive::Rows => (&mut self.rows[..], &self.cols[..]),
+        };
+        return MatrixState::read(self.wiring.drive, |line| {
+            strobe(&mut driven[line], true, polarity);
+            let mut seen: u32 = 0;
+            for (i, input) in sensed.iter().enumerate() {
+                if sense(input, polarity) { seen |= 1 << i; }
             }
-            i += 1;
-        }
-        return 0;
+            strobe(&mut driven[line], false, polarity);
+            seen
+        });
     }
 
-    pub fn is_digit(&mut self, s: u8) -> bool {
-        return ((s%5>1)&&(s<15)) || (s==10);
+    /// Reads the matrix and decodes it through the keymap.
+    pub fn scan(&mut self) -> Scan {
+        return self.read_matrix().decode(&self.keymap);
     }
 }
+
+#[cfg(feature = "stm32")]
+impl <'d, const ROW: usize, const COL: usize> KeySource for Keyboard<'d, ROW, COL>{
+    fn scan(&mut self) -> Scan { return Keyboard::scan(self); }
+}
 /*
 impl <'d, I1: Pin, I2: Pin, I3: Pin, I4: Pin, I5: Pin, O1: Pin, O2: Pin, O3: Pin, O4: Pin>Keyboard <'d, I1, I2, I3, I4, I5, O1, O2, O3, O4>{
     pub fn new(i1: I1, i2: I2, i3: I3, i4: I4, i5: I5, o1: O1, o2: O2, o3: O3, o4: O4) -> Keyboard<'d ,I1, I2, I3, I4, I5, O1, O2, O3, O4>{
@@ -0,0 +1,131 @@
+/// Returned when a full map is asked to take a new key.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct CapacityError;
+
+/// Heapless map with room for `N` entries, e.g. `FixedMap<Key, char, 16>`
+/// for a key-to-character table. Entries are kept packed at the front, so
+/// lookups are a linear search over `len` slots.
+#[derive(Copy, Clone)]
+pub struct FixedMap<K, V, const N: usize> {
+    entries: [Option<(K, V)>; N],
+    len: usize,
+}
+
+impl<K: Copy + PartialEq, V: Copy, const N: usize> FixedMap<K, V, N> {
+    pub const fn new() -> Self { Self { entries: [None; N], len: 0 } }
+
+    /// Builds a map from `pairs`; later duplicates overwrite earlier ones.
+    pub fn from_pairs(pairs: &[(K, V)]) -> Result<Self, CapacityError> {
+        let mut res = Self::new();
+        for (key, value) in pairs {
+            res.insert(*key, *value)?;
+        }
+        return Ok(res);
+    }
+
+    fn slot(&self, key: &K) -> Option<usize> {
+        for i in 0..self.len {
+            if let Some((k, _)) = &self.entries[i] {
+                if k == key { return Some(i); }
+            }
+        }
+        return None;
+    }
+
+    /// Stores `value` under `key` and returns the value it replaced. A new
+    /// key in a full map is rejected and the map is left untouched.
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, CapacityError> {
+        if let Some(i) = self.slot(&key) {
+            let old = self.entries[i].map(|(_, v)| v);
+            self.entries[i] = Some((key, value));
+            return Ok(old);
+        }
+        if self.len == N { return Err(CapacityError); }
+        self.entries[self.len] = Some((key, value));
+        self.len += 1;
+        return Ok(None);
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let i = self.slot(key)?;
+        return self.entries[i].as_ref().map(|(_, v)| v);
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let i = self.slot(key)?;
+        return self.entries[i].as_mut().map(|(_, v)| v);
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool { return self.slot(key).is_some(); }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let i = self.slot(key)?;
+        let old = self.entries[i].map(|(_, v)| v);
+        self.len -= 1;
+        self.entries[i] = self.entries[self.len];
+        self.entries[self.len] = None;
+        return old;
+    }
+
+    pub fn clear(&mut self) {
+        self.entries = [None; N];
+        self.len = 0;
+    }
+
+    pub fn len(&self) -> usize { return self.len; }
+
+    pub fn is_empty(&self) -> bool { return self.len == 0; }
+
+    pub const fn capacity(&self) -> usize { N }
+
+    /// Entries in insertion order, except that `remove` moves the last entry
+    /// into the freed slot.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        return self.entries[..self.len].iter().filter_map(|e| e.as_ref().map(|(k, v)| (k, v)));
+    }
+}
+
+impl<K: Copy + PartialEq, V: Copy, const N: usize> Default for FixedMap<K, V, N> {
+    fn default() -> Self { Self::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Key;
+
+    #[test]
+    fn new_key_in_full_map_is_rejected() {
+        let mut map: FixedMap<Key, char, 2> = FixedMap::from_pairs(&[(Key::Digit(1), 'a'), (Key::Digit(2), 'b')]).unwrap();
+        assert_eq!(map.insert(Key::Digit(3), 'c'), Err(CapacityError));
+        assert_eq!(map.len(), 2);
+        assert!(!map.contains_key(&Key::Digit(3)));
+        assert!(FixedMap::<Key, char, 1>::from_pairs(&[(Key::Up, 'u'), (Key::Down, 'd')]).is_err());
+    }
+
+    #[test]
+    fn existing_key_in_full_map_is_overwritten() {
+        let mut map: FixedMap<Key, char, 2> = FixedMap::from_pairs(&[(Key::Digit(1), 'a'), (Key::Digit(2), 'b')]).unwrap();
+        assert_eq!(map.insert(Key::Digit(2), 'x'), Ok(Some('b')));
+        assert_eq!(map.get(&Key::Digit(2)), Some(&'x'));
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn later_duplicates_win() {
+        let map: FixedMap<char, u8, 2> = FixedMap::from_pairs(&[('a', 1), ('a', 2)]).unwrap();
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get(&'a'), Some(&2));
+    }
+
+    #[test]
+    fn remove_keeps_entries_packed() {
+        let mut map: FixedMap<char, u8, 3> = FixedMap::from_pairs(&[('a', 1), ('b', 2), ('c', 3)]).unwrap();
+        assert_eq!(map.remove(&'a'), Some(1));
+        assert_eq!(map.remove(&'a'), None);
+        assert_eq!(map.len(), 2);
+        assert!(map.iter().eq([(&'c', &3), (&'b', &2)]));
+        assert_eq!(map.insert('d', 4), Ok(None));
+        assert_eq!(map.get(&'d'), Some(&4));
+    }
+}
@@ -0,0 +1,192 @@
+use crate::{Keymap, KeySet, Scan};
+
+/// Lines strobed during a scan; the other side is sensed. With diodes in the
+/// matrix, current only flows from the driven side, so this follows the
+/// diode direction.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Drive {
+    Columns,
+    Rows,
+}
+
+/// Level of a strobed line and of a sensed line on a pressed key.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Polarity {
+    /// Strobe high, sense with pull-down.
+    ActiveHigh,
+    /// Strobe low, sense with pull-up.
+    ActiveLow,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Wiring {
+    pub drive: Drive,
+    pub polarity: Polarity,
+}
+
+impl Wiring {
+    pub const fn new(drive: Drive, polarity: Polarity) -> Self { Self { drive, polarity } }
+}
+
+/// Wiring of the game board: columns strobed high, rows pulled down.
+pub const COLUMNS_ACTIVE_HIGH: Wiring = Wiring::new(Drive::Columns, Polarity::ActiveHigh);
+
+/// Pressed keys of one matrix read, indexed `pressed[row][col]` like `Keymap`,
+/// with wiring and polarity already resolved.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct MatrixState<const ROW: usize, const COL: usize> {
+    pub pressed: [[bool; COL]; ROW],
+}
+
+impl<const ROW: usize, const COL: usize> MatrixState<ROW, COL> {
+    pub const fn empty() -> Self { Self { pressed: [[false; COL]; ROW] } }
+
+    pub fn set(&mut self, row: usize, col: usize) { self.pressed[row][col] = true; }
+
+    /// Reads a matrix one driven line at a time: `strobe(line)` activates
+    /// driven line `line` and returns a bit for each sensed line that saw
+    /// it, bit `i` for sensed line `i`. Which side is which follows `drive`.
+    pub fn read(drive: Drive, mut strobe: impl FnMut(usize) -> u32) -> Self {
+        let mut state = Self::empty();
+        match drive {
+            Drive::Columns => {
+                for col in 0..COL {
+                    let seen = strobe(col);
+                    for row in 0..ROW {
+                        if seen & (1 << row) != 0 { state.set(row, col); }
+                    }
+                }
+            }
+            Drive::Rows => {
+                for row in 0..ROW {
+                    let seen = strobe(row);
+                    for col in 0..COL {
+                        if seen & (1 << col) != 0 { state.set(row, col); }
+                    }
+                }
+            }
+        }
+        return state;
+    }
+
+    /// Translates the state through `keymap`. If two rows see the same two
+    /// columns, the four keys form a rectangle and any of them may be a
+    /// phantom, so the scan is flagged as ghosted.
+    pub fn decode(&self, keymap: &Keymap<ROW, COL>) -> Scan {
+        let mut keys = KeySet::empty();
+        let mut cols: [u32; ROW] = [0; ROW];
+        for row in 0..ROW {
+            for col in 0..COL {
+                if self.pressed[row][col] {
+                    keys.insert(keymap.get(row, col));
+                    cols[row] |= 1 << col;
+                }
+            }
+        }
+        let mut ghosted = false;
+        for row in 0..ROW {
+            for other in row+1..ROW {
+                if (cols[row] & cols[other]).count_ones() >= 2 { ghosted = true; }
+            }
+        }
+        return Scan { keys, ghosted };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Key, KEYPAD_5X4};
+
+    /// Labels printed on the board's keypad, row by row.
+    const LABELS: [[&str; 4]; 5] = [
+        ["F1", "F2", "#", "*"],
+        ["1", "2", "3", "^"],
+        ["4", "5", "6", "v"],
+        ["7", "8", "9", "Esc"],
+        ["<-", "0", "->", "Ent"],
+    ];
+
+    fn label(key: Key) -> &'static str {
+        match key {
+            Key::Digit(d) => ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"][d as usize],
+            Key::F1 => "F1",
+            Key::F2 => "F2",
+            Key::Hash => "#",
+            Key::Star => "*",
+            Key::Up => "^",
+            Key::Down => "v",
+            Key::Left => "<-",
+            Key::Right => "->",
+            Key::Esc => "Esc",
+            Key::Ent => "Ent",
+            Key::A | Key::B | Key::C | Key::D => "?",
+        }
+    }
+
+    /// Reads a diode-less matrix with `held` keys down. A strobed line
+    /// reaches every line connected to it through held keys, so three
+    /// corners of a rectangle also close the fourth.
+    fn read(held: &[(usize, usize)], drive: Drive) -> MatrixState<5, 4> {
+        return MatrixState::read(drive, |line| {
+            let (mut rows, mut cols): (u32, u32) = match drive {
+                Drive::Columns => (0, 1 << line),
+                Drive::Rows => (1 << line, 0),
+            };
+            loop {
+                let (before_rows, before_cols) = (rows, cols);
+                for (row, col) in held {
+                    if rows & (1 << row) != 0 || cols & (1 << col) != 0 {
+                        rows |= 1 << row;
+                        cols |= 1 << col;
+                    }
+                }
+                if (rows, cols) == (before_rows, before_cols) { break; }
+            }
+            match drive {
+                Drive::Columns => rows,
+                Drive::Rows => cols,
+            }
+        });
+    }
+
+    #[test]
+    fn every_position_decodes_to_its_label() {
+        for drive in [Drive::Columns, Drive::Rows] {
+            for row in 0..5 {
+                for col in 0..4 {
+                    let scan = read(&[(row, col)], drive).decode(&KEYPAD_5X4);
+                    assert_eq!(scan.keys.len(), 1);
+                    assert_eq!(label(scan.single().unwrap()), LABELS[row][col]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn driving_rows_or_columns_reads_the_same_state() {
+        let held = [(0, 0), (1, 2), (4, 3)];
+        let columns = read(&held, Drive::Columns);
+        assert_eq!(columns, read(&held, Drive::Rows));
+        let mut expected = MatrixState::<5, 4>::empty();
+        for (row, col) in held { expected.set(row, col); }
+        assert_eq!(columns, expected);
+    }
+
+    #[test]
+    fn chord_without_shared_lines_is_not_ghosted() {
+        let scan = read(&[(0, 0), (0, 1)], Drive::Columns).decode(&KEYPAD_5X4);
+        assert!(!scan.ghosted);
+        assert!(scan.is_chord(crate::KeySet::of(&[Key::F1, Key::F2])));
+    }
+
+    #[test]
+    fn three_corners_of_a_rectangle_are_ghosted() {
+        for drive in [Drive::Columns, Drive::Rows] {
+            let scan = read(&[(1, 0), (1, 1), (2, 0)], drive).decode(&KEYPAD_5X4);
+            assert!(scan.ghosted);
+            assert!(scan.keys.contains(Key::Digit(5)));
+            assert_eq!(scan.single(), None);
+        }
+    }
+}
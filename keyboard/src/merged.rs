@@ -0,0 +1,46 @@
+use crate::{KeySource, Scan};
+
+/// Two key sources read as one, e.g. the keypad and a `RotaryEncoder`, so
+/// that menus work with either. Their scans are merged.
+pub struct Merged<A: KeySource, B: KeySource>(pub A, pub B);
+
+impl<A: KeySource, B: KeySource> KeySource for Merged<A, B> {
+    fn scan(&mut self) -> Scan {
+        let a = self.0.scan();
+        let b = self.1.scan();
+        return Scan { keys: a.keys.union(b.keys), ghosted: a.ghosted || b.ghosted };
+    }
+
+    /// The later change of the two sources.
+    fn last_change(&self) -> Option<u64> { return self.0.last_change().max(self.1.last_change()); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Key, KeyEvent, KeySet, MockKeyboard, Replay};
+
+    fn event(at: u64, keys: &[Key]) -> KeyEvent {
+        return KeyEvent { at, scan: Scan { keys: KeySet::of(keys), ghosted: false } };
+    }
+
+    #[test]
+    fn last_change_is_the_later_of_the_two() {
+        let a = [event(10, &[Key::Up]), event(40, &[])];
+        let b = [event(25, &[Key::Ent]), event(30, &[])];
+        let mut merged = Merged(Replay::stepped(&a), Replay::stepped(&b));
+        assert_eq!(merged.last_change(), None);
+        assert!(merged.scan().keys == KeySet::of(&[Key::Up, Key::Ent]));
+        assert_eq!(merged.last_change(), Some(25));
+        merged.scan();
+        assert_eq!(merged.last_change(), Some(40));
+    }
+
+    #[test]
+    fn source_without_times_takes_the_other_ones() {
+        let b = [event(25, &[Key::Ent])];
+        let mut merged = Merged(MockKeyboard::keys(&[Key::Up]), Replay::stepped(&b));
+        merged.scan();
+        assert_eq!(merged.last_change(), Some(25));
+    }
+}
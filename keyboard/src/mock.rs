@@ -0,0 +1,94 @@
+use embassy_time::{Duration, Instant};
+use crate::{Key, KeySet, KeySource, Scan};
+
+/// How long a scripted step stays on the matrix.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Hold {
+    /// For this many scans, independent of time.
+    Scans(u16),
+    /// Until this much time has passed since the step began.
+    Time(Duration),
+}
+
+/// One step of a mock script: the keys held and for how long.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub keys: KeySet,
+    pub hold: Hold,
+}
+
+impl Step {
+    /// `key` held for one scan.
+    pub const fn press(key: Key) -> Self { Self { keys: KeySet::of(&[key]), hold: Hold::Scans(1) } }
+
+    /// `keys` held together for one scan.
+    pub const fn chord(keys: &[Key]) -> Self { Self { keys: KeySet::of(keys), hold: Hold::Scans(1) } }
+
+    /// Nothing held for one scan.
+    pub const fn release() -> Self { Self { keys: KeySet::empty(), hold: Hold::Scans(1) } }
+
+    pub const fn scans(self, scans: u16) -> Self { Self { keys: self.keys, hold: Hold::Scans(scans) } }
+
+    pub const fn lasting(self, time: Duration) -> Self { Self { keys: self.keys, hold: Hold::Time(time) } }
+}
+
+enum Script<'a> {
+    Keys(&'a [Key]),
+    Steps(&'a [Step]),
+}
+
+/// Scripted key source for driving the UI without a keypad.
+///
+/// `MockKeyboard::keys` presses each key for one scan and releases it for
+/// the next, which is one `get_pressed` per key. `MockKeyboard::steps` plays
+/// explicit steps, including chords and timed holds. Once the script is
+/// over, nothing is held.
+pub struct MockKeyboard<'a> {
+    script: Script<'a>,
+    pos: usize,
+    scans: u16,
+    since: Option<Instant>,
+}
+
+impl<'a> MockKeyboard<'a> {
+    pub fn keys(keys: &'a [Key]) -> Self { Self { script: Script::Keys(keys), pos: 0, scans: 0, since: None } }
+
+    pub fn steps(steps: &'a [Step]) -> Self { Self { script: Script::Steps(steps), pos: 0, scans: 0, since: None } }
+
+    /// `true` once the whole script has been played.
+    pub fn is_done(&self) -> bool {
+        match self.script {
+            Script::Keys(keys) => self.pos >= 2 * keys.len(),
+            Script::Steps(steps) => self.pos >= steps.len(),
+        }
+    }
+
+    fn step(&self) -> Option<Step> {
+        match self.script {
+            Script::Keys(keys) => {
+                let key = keys.get(self.pos / 2)?;
+                if self.pos % 2 == 0 { Some(Step::press(*key)) } else { Some(Step::release()) }
+            }
+            Script::Steps(steps) => steps.get(self.pos).copied(),
+        }
+    }
+}
+
+impl<'a> KeySource for MockKeyboard<'a> {
+    fn scan(&mut self) -> Scan {
+        let Some(step) = self.step() else { return Scan::default(); };
+        let over = match step.hold {
+            Hold::Scans(n) => {
+                self.scans += 1;
+                self.scans >= n
+            }
+            Hold::Time(time) => self.since.get_or_insert_with(Instant::now).elapsed() >= time,
+        };
+        if over {
+            self.pos += 1;
+            self.scans = 0;
+            self.since = None;
+        }
+        return Scan { keys: step.keys, ghosted: false };
+    }
+}
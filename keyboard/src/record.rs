@@ -0,0 +1,165 @@
+use embassy_time::Instant;
+use crate::{KeySource, Scan};
+
+/// Change of the held keys, `at` milliseconds after recording started.
+/// A release shows up as an event with no keys.
+#[derive(Copy, Clone, PartialEq, Eq, Default, defmt::Format)]
+pub struct KeyEvent {
+    pub at: u64,
+    pub scan: Scan,
+}
+
+/// Wraps a key source and keeps its last `N` key events in a ring buffer,
+/// together with the RNG seed the session started from.
+pub struct Recorder<S: KeySource, const N: usize> {
+    source: S,
+    events: [KeyEvent; N],
+    head: usize,
+    len: usize,
+    start: Instant,
+    last: Scan,
+    changed: Option<u64>,
+    seed: u64,
+}
+
+impl<S: KeySource, const N: usize> Recorder<S, N> {
+    pub fn new(source: S, seed: u64) -> Self {
+        Self { source, events: [KeyEvent::default(); N], head: 0, len: 0, start: Instant::now(), last: Scan::default(), changed: None, seed }
+    }
+
+    fn push(&mut self, event: KeyEvent) {
+        self.changed = Some(event.at);
+        self.events[(self.head + self.len) % N] = event;
+        if self.len < N { self.len += 1; } else { self.head = (self.head + 1) % N; }
+    }
+
+    pub fn seed(&self) -> u64 { return self.seed; }
+
+    /// Recorded events, oldest first.
+    pub fn events(&self) -> impl Iterator<Item = &KeyEvent> {
+        return (0..self.len).map(move |i| &self.events[(self.head + i) % N]);
+    }
+
+    /// Copies the recorded events, oldest first, into `out` and returns how many fit.
+    pub fn copy_to(&self, out: &mut [KeyEvent]) -> usize {
+        let mut count: usize = 0;
+        for (slot, event) in out.iter_mut().zip(self.events()) {
+            *slot = *event;
+            count += 1;
+        }
+        return count;
+    }
+
+    /// Prints the seed and every event over defmt, one per line.
+    pub fn dump(&self) {
+        defmt::println!("replay seed {} events {}", self.seed, self.len);
+        for event in self.events() {
+            defmt::println!("{}", event);
+        }
+    }
+
+    /// Forgets the recorded events and starts a new session from `seed`.
+    pub fn restart(&mut self, seed: u64) {
+        self.head = 0; self.len = 0;
+        self.start = Instant::now();
+        self.last = Scan::default();
+        self.changed = None;
+        self.seed = seed;
+    }
+
+    pub fn source(&mut self) -> &mut S { return &mut self.source; }
+}
+
+impl<S: KeySource, const N: usize> KeySource for Recorder<S, N> {
+    fn scan(&mut self) -> Scan {
+        let scan = self.source.scan();
+        if scan != self.last {
+            self.push(KeyEvent { at: self.start.elapsed().as_millis(), scan });
+            self.last = scan;
+        }
+        return scan;
+    }
+
+    fn last_change(&self) -> Option<u64> { return self.changed; }
+}
+
+/// Plays back recorded events as a key source.
+///
+/// Timed playback reproduces the recorded timing against `Instant::now()`.
+/// Stepped playback ignores timestamps and moves to the next event on every
+/// scan, which is what a host test wants.
+pub struct Replay<'a> {
+    events: &'a [KeyEvent],
+    pos: usize,
+    current: Scan,
+    start: Option<Instant>,
+    timed: bool,
+}
+
+impl<'a> Replay<'a> {
+    pub fn timed(events: &'a [KeyEvent]) -> Self {
+        Self { events, pos: 0, current: Scan::default(), start: None, timed: true }
+    }
+
+    pub fn stepped(events: &'a [KeyEvent]) -> Self {
+        Self { events, pos: 0, current: Scan::default(), start: None, timed: false }
+    }
+
+    /// `true` once every event has been played.
+    pub fn is_done(&self) -> bool { return self.pos >= self.events.len(); }
+}
+
+impl<'a> KeySource for Replay<'a> {
+    fn scan(&mut self) -> Scan {
+        if !self.timed {
+            if let Some(event) = self.events.get(self.pos) {
+                self.current = event.scan;
+                self.pos += 1;
+            } else {
+                self.current = Scan::default();
+            }
+            return self.current;
+        }
+        let start = *self.start.get_or_insert_with(Instant::now);
+        let now = start.elapsed().as_millis();
+        while self.pos < self.events.len() && self.events[self.pos].at <= now {
+            self.current = self.events[self.pos].scan;
+            self.pos += 1;
+        }
+        return self.current;
+    }
+
+    /// Time the last played event was recorded at, in either playback.
+    fn last_change(&self) -> Option<u64> {
+        return self.pos.checked_sub(1).map(|i| self.events[i].at);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Key, MockKeyboard};
+
+    /// `last_change` after each press of `source`, up to `N` presses.
+    fn changes<const N: usize>(source: &mut impl KeySource) -> [Option<u64>; N] {
+        return core::array::from_fn(|_| {
+            source.wait_press();
+            source.last_change()
+        });
+    }
+
+    #[test]
+    fn replay_reports_the_recorded_times() {
+        let keys = [Key::Digit(1), Key::Up, Key::Ent];
+        let mut recorder: Recorder<_, 16> = Recorder::new(MockKeyboard::keys(&keys), 7);
+        assert_eq!(recorder.last_change(), None);
+        let recorded: [Option<u64>; 3] = changes(&mut recorder);
+        let mut events = [KeyEvent::default(); 16];
+        let count = recorder.copy_to(&mut events);
+        assert_eq!(count, 6);
+        assert_eq!(recorded[2], Some(events[5].at));
+        let mut replay = Replay::stepped(&events[..count]);
+        assert_eq!(changes::<3>(&mut replay), recorded);
+        assert!(replay.is_done());
+    }
+}
@@ -0,0 +1,135 @@
+use crate::Key;
+
+/// Set of keys held at once, one bit per `Key::index`.
+#[derive(Copy, Clone, PartialEq, Eq, Default, defmt::Format)]
+pub struct KeySet(u32);
+
+impl KeySet {
+    pub const fn empty() -> Self { KeySet(0) }
+
+    /// Builds a set from keys, e.g. `KeySet::of(&[Key::F1, Key::F2])`.
+    pub const fn of(keys: &[Key]) -> Self {
+        let mut bits: u32 = 0;
+        let mut i: usize = 0;
+        while i < keys.len() {
+            bits |= 1 << keys[i].index();
+            i += 1;
+        }
+        KeySet(bits)
+    }
+
+    pub fn insert(&mut self, key: Key) { self.0 |= 1 << key.index(); }
+
+    pub fn contains(&self, key: Key) -> bool { return self.0 & (1 << key.index()) != 0; }
+
+    /// `true` when every key of `other` is also in this set.
+    pub fn contains_all(&self, other: KeySet) -> bool { return self.0 & other.0 == other.0; }
+
+    pub fn union(self, other: KeySet) -> KeySet { KeySet(self.0 | other.0) }
+
+    pub fn intersection(self, other: KeySet) -> KeySet { KeySet(self.0 & other.0) }
+
+    /// Keys of this set that are not in `other`.
+    pub fn without(self, other: KeySet) -> KeySet { KeySet(self.0 & !other.0) }
+
+    pub fn len(&self) -> u32 { return self.0.count_ones(); }
+
+    pub fn is_empty(&self) -> bool { return self.0 == 0; }
+
+    /// Key with the lowest index in the set.
+    pub fn first(&self) -> Option<Key> {
+        if self.0 == 0 { return None; }
+        return Key::from_index(self.0.trailing_zeros() as u8);
+    }
+}
+
+/// Result of one matrix scan: every key seen as pressed, plus whether the
+/// pattern is ambiguous because of ghosting.
+#[derive(Copy, Clone, PartialEq, Eq, Default, defmt::Format)]
+pub struct Scan {
+    pub keys: KeySet,
+    pub ghosted: bool,
+}
+
+impl Scan {
+    /// The pressed key when exactly one key is down and the scan is not
+    /// ghosted.
+    pub fn single(&self) -> Option<Key> {
+        if self.ghosted || self.keys.len() != 1 { return None; }
+        return self.keys.first();
+    }
+
+    /// `true` when exactly the keys of `chord` are down and the scan is not ghosted.
+    pub fn is_chord(&self, chord: KeySet) -> bool { return !self.ghosted && self.keys == chord; }
+}
+
+/// Turns scans into key presses, for typing.
+///
+/// Each key is reported once, when it goes down, even while other keys are
+/// still held, so a key pressed before the last one is let go is not lost.
+/// Keys that go down in the same scan come out one per call, lowest index
+/// first, while they are still held. Ghosted scans report nothing; their
+/// keys are reported once the ghosting clears. The `wait_key` family keeps
+/// one of these between calls.
+#[derive(Copy, Clone, PartialEq, Eq, Default)]
+pub struct Presses {
+    /// Keys held at the last scan that were already reported.
+    held: KeySet,
+}
+
+impl Presses {
+    pub const fn new() -> Self { Self { held: KeySet::empty() } }
+
+    /// The key of `scan` that went down since the scans before it, if any.
+    pub fn next(&mut self, scan: Scan) -> Option<Key> {
+        self.held = self.held.intersection(scan.keys);
+        if scan.ghosted { return None; }
+        let key = scan.keys.without(self.held).first()?;
+        self.held.insert(key);
+        return Some(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(keys: &[Key]) -> Scan { return Scan { keys: KeySet::of(keys), ghosted: false }; }
+
+    /// Keys `Presses` reports for `scans`, in order.
+    fn presses(scans: &[Scan]) -> [Option<Key>; 8] {
+        let mut presses = Presses::new();
+        let mut res = [None; 8];
+        for (i, scan) in scans.iter().enumerate() { res[i] = presses.next(*scan); }
+        return res;
+    }
+
+    #[test]
+    fn rolled_over_keys_are_both_reported() {
+        let (one, two) = (Key::Digit(1), Key::Digit(2));
+        let res = presses(&[scan(&[one]), scan(&[one, two]), scan(&[two]), scan(&[])]);
+        assert_eq!(res[..4], [Some(one), Some(two), None, None]);
+    }
+
+    #[test]
+    fn held_key_is_reported_once() {
+        let one = Key::Digit(1);
+        let res = presses(&[scan(&[one]), scan(&[one]), scan(&[]), scan(&[one])]);
+        assert_eq!(res[..4], [Some(one), None, None, Some(one)]);
+    }
+
+    #[test]
+    fn keys_down_together_come_out_one_at_a_time() {
+        let both = scan(&[Key::Up, Key::Digit(3)]);
+        let res = presses(&[both, both, both]);
+        assert_eq!(res[..3], [Some(Key::Digit(3)), Some(Key::Up), None]);
+    }
+
+    #[test]
+    fn ghosted_scan_waits_for_the_ghosting_to_clear() {
+        let mut ghosted = scan(&[Key::Digit(1), Key::Digit(2), Key::Digit(4)]);
+        ghosted.ghosted = true;
+        let res = presses(&[ghosted, scan(&[Key::Digit(2)])]);
+        assert_eq!(res[..2], [None, Some(Key::Digit(2))]);
+    }
+}
@@ -0,0 +1,38 @@
+use embassy_time::{with_timeout, Duration, Timer};
+use crate::{Key, KeySource, Presses, Scan};
+
+/// Pause between scans while waiting asynchronously.
+pub const POLL: Duration = Duration::from_millis(1);
+
+/// Async `KeySource::wait_press`: scans every `POLL` and lets other tasks run
+/// in between. Dropping the future cancels the wait; a key held at that
+/// moment is seen again by the next wait.
+pub async fn wait_press<S: KeySource>(source: &mut S) -> Scan {
+    let mut scan = source.scan();
+    while scan.keys.is_empty() {
+        Timer::after(POLL).await;
+        scan = source.scan();
+    }
+    let mut res = scan;
+    while !scan.keys.is_empty() {
+        Timer::after(POLL).await;
+        scan = source.scan();
+        res.keys = res.keys.union(scan.keys);
+        res.ghosted |= scan.ghosted;
+    }
+    return res;
+}
+
+/// Async `KeySource::wait_key`. Dropping the future cancels the wait
+/// without losing a key.
+pub async fn wait_key<S: KeySource>(source: &mut S, presses: &mut Presses) -> Key {
+    loop {
+        if let Some(key) = presses.next(source.scan()) { return key; }
+        Timer::after(POLL).await;
+    }
+}
+
+/// `wait_key` bounded by `embassy_time::with_timeout`, `None` on timeout.
+pub async fn wait_key_cancellable<S: KeySource>(source: &mut S, presses: &mut Presses, timeout: Duration) -> Option<Key> {
+    return with_timeout(timeout, wait_key(source, presses)).await.ok();
+}
@@ -1,4 +1,5 @@
 MEMORY {
-    FLASH : ORIGIN = 0x08000000, LENGTH = 512K
+    /* The last two 128K sectors hold the saved settings and high scores. */
+    FLASH : ORIGIN = 0x08000000, LENGTH = 256K
     RAM : ORIGIN = 0x20000000, LENGTH = 128K
 }
\ No newline at end of file
@@ -0,0 +1,172 @@
+#![no_std]
+// Every function ends in an explicit `return`, as in the rest of the firmware.
+#![allow(clippy::needless_return)]
+
+//! Rules of the memory game, free of any hardware, so they build and run on
+//! the host as well as on the board. The unit tests run on the host with
+//! `cargo test-host`, short for
+//! `cargo test --target x86_64-unknown-linux-gnu -p memory_core` and the
+//! other host-buildable crates.
+
+mod crc;
+mod recall;
+mod rng;
+mod round;
+mod score;
+mod sequence;
+mod settings;
+mod staircase;
+mod storage;
+mod store;
+pub use crc::crc32;
+pub use recall::Recall;
+pub use rng::Rng;
+pub use round::{Rounds, Verdict};
+pub use score::{HighScore, HighScores, Score, Scoring, HIGH_SCORE, TIME_BONUS};
+pub use sequence::{Flash, Sequence, MAX_LEN};
+pub use staircase::Staircase;
+pub use settings::{Settings, MAX_BRIGHTNESS, SETTINGS, SETTINGS_VERSION};
+pub use storage::{Log, OutOfBounds, RamFlash, Record, Storage, RECORD_SIZE};
+pub use store::Store;
+
+/// Number of symbols, and of cells they can be shown in.
+pub const SYMBOLS: u8 = 16;
+pub const MIN_DIFFICULTY: u8 = 1;
+pub const MAX_DIFFICULTY: u8 = 16;
+
+/// How rounds follow each other.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub enum Mode {
+    /// Every round is a new sequence of `Rules::length` symbols.
+    #[default]
+    Classic,
+    /// Every round repeats the last sequence with one more symbol; the
+    /// score is the longest chain reproduced.
+    Simon,
+}
+
+impl Mode {
+    pub const COUNT: u8 = 2;
+
+    pub fn index(self) -> u8 { return self as u8; }
+
+    pub fn from_index(index: u8) -> Option<Mode> {
+        match index {
+            0 => Some(Mode::Classic),
+            1 => Some(Mode::Simon),
+            _ => None,
+        }
+    }
+}
+
+/// Settings a round is played with.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Rules {
+    /// `MIN_DIFFICULTY..=MAX_DIFFICULTY`.
+    pub difficulty: u8,
+    /// Each symbol is shown in its own cell rather than in a random one.
+    pub fixed: bool,
+    pub mode: Mode,
+    pub recall: Recall,
+    /// The answer has to be in within `answer_ms`.
+    pub timed: bool,
+    /// `difficulty` is set round by round by a `Staircase`, and also scales
+    /// the gap between symbols and how many symbols are used.
+    pub adaptive: bool,
+}
+
+impl Rules {
+    pub const fn new(difficulty: u8, fixed: bool) -> Self { Self { difficulty, fixed, mode: Mode::Classic, recall: Recall::Forward, timed: false, adaptive: false } }
+
+    /// Symbols in a classic sequence, and in the first of a Simon chain: 3,
+    /// plus one every two difficulty levels.
+    pub fn length(&self) -> usize {
+        return 3 + (self.difficulty.max(MIN_DIFFICULTY) as usize - 1) / 2;
+    }
+
+    /// How long each symbol stays lit; even levels show twice as fast.
+    /// Adaptive rules go from 1000 ms down to 400 ms in even steps.
+    pub fn show_ms(&self) -> u64 {
+        if self.adaptive { return 1000 - 40 * self.steps(); }
+        if self.difficulty.is_multiple_of(2) { return 500; }
+        return 1000;
+    }
+
+    /// Blank time between two symbols; adaptive rules go from 300 ms down
+    /// to 120 ms.
+    pub fn gap_ms(&self) -> u64 {
+        if self.adaptive { return 300 - 12 * self.steps(); }
+        return 200;
+    }
+
+    /// Symbols a sequence is drawn from, `1..=symbols()`. Adaptive rules
+    /// start with 4 and add one a level.
+    pub fn symbols(&self) -> u8 {
+        if self.adaptive { return (4 + self.steps() as u8).min(SYMBOLS); }
+        return SYMBOLS;
+    }
+
+    /// Time for a timed answer of `len` symbols: 2 s, plus 2 s a symbol at
+    /// the lowest difficulty, down to 875 ms a symbol at the highest.
+    pub fn answer_ms(&self, len: usize) -> u64 {
+        return 2000 + len as u64 * (2000 - 75 * self.steps());
+    }
+
+    /// Levels above the lowest.
+    fn steps(&self) -> u64 {
+        return (self.difficulty.clamp(MIN_DIFFICULTY, MAX_DIFFICULTY) - MIN_DIFFICULTY) as u64;
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self { Self::new(2, true) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(difficulty: u8, adaptive: bool) -> Rules {
+        return Rules { adaptive, ..Rules::new(difficulty, true) };
+    }
+
+    #[test]
+    fn length_grows_every_two_levels() {
+        assert_eq!(rules(MIN_DIFFICULTY, false).length(), 3);
+        assert_eq!(rules(2, false).length(), 3);
+        assert_eq!(rules(3, false).length(), 4);
+        assert_eq!(rules(MAX_DIFFICULTY, false).length(), 10);
+        // Out-of-range levels are read as the lowest.
+        assert_eq!(rules(0, false).length(), 3);
+    }
+
+    #[test]
+    fn timing_at_the_lowest_and_highest_level() {
+        let (low, high) = (rules(MIN_DIFFICULTY, false), rules(MAX_DIFFICULTY, false));
+        assert_eq!((low.show_ms(), high.show_ms()), (1000, 500));
+        assert_eq!((low.gap_ms(), high.gap_ms()), (200, 200));
+        assert_eq!((low.symbols(), high.symbols()), (SYMBOLS, SYMBOLS));
+        assert_eq!(low.answer_ms(3), 2000 + 3 * 2000);
+        assert_eq!(high.answer_ms(10), 2000 + 10 * 875);
+        assert_eq!(high.answer_ms(0), 2000);
+    }
+
+    #[test]
+    fn adaptive_timing_at_the_lowest_and_highest_level() {
+        let (low, high) = (rules(MIN_DIFFICULTY, true), rules(MAX_DIFFICULTY, true));
+        assert_eq!((low.show_ms(), high.show_ms()), (1000, 400));
+        assert_eq!((low.gap_ms(), high.gap_ms()), (300, 120));
+        assert_eq!((low.symbols(), high.symbols()), (4, SYMBOLS));
+        // Past the range, the timing stays at the edge.
+        assert_eq!(rules(MAX_DIFFICULTY + 1, true).show_ms(), 400);
+        assert_eq!(rules(0, true).gap_ms(), 300);
+    }
+
+    #[test]
+    fn indices_round_trip() {
+        for i in 0..Mode::COUNT { assert_eq!(Mode::from_index(i).map(Mode::index), Some(i)); }
+        for i in 0..Recall::COUNT { assert_eq!(Recall::from_index(i).map(Recall::index), Some(i)); }
+        assert_eq!(Mode::from_index(Mode::COUNT), None);
+        assert_eq!(Recall::from_index(Recall::COUNT), None);
+    }
+}
@@ -0,0 +1,47 @@
+/// What the player enters for a shown sequence.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub enum Recall {
+    /// The symbols as shown.
+    #[default]
+    Forward,
+    /// The symbols last to first.
+    Reverse,
+    /// The symbols in ascending order.
+    Sorted,
+    /// Each symbol one higher, the highest wrapping to 1.
+    PlusOne,
+    /// Only the LEDs above the cells light up, and the player enters the
+    /// cells, numbered from 1, in the order they lit.
+    Position,
+}
+
+impl Recall {
+    pub const COUNT: u8 = 5;
+
+    pub fn index(self) -> u8 { return self as u8; }
+
+    pub fn from_index(index: u8) -> Option<Recall> {
+        match index {
+            0 => Some(Recall::Forward),
+            1 => Some(Recall::Reverse),
+            2 => Some(Recall::Sorted),
+            3 => Some(Recall::PlusOne),
+            4 => Some(Recall::Position),
+            _ => None,
+        }
+    }
+
+    /// Turns the shown `symbols`, drawn from `1..=count`, into the expected
+    /// answer, in place. `Position` answers are cells, not symbols, and are
+    /// left alone.
+    pub fn apply(self, symbols: &mut [u8], count: u8) {
+        match self {
+            Recall::Forward | Recall::Position => {}
+            Recall::Reverse => { symbols.reverse(); }
+            Recall::Sorted => { symbols.sort_unstable(); }
+            Recall::PlusOne => {
+                for symbol in symbols.iter_mut() { *symbol = *symbol % count + 1; }
+            }
+        }
+    }
+}
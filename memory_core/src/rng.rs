@@ -0,0 +1,37 @@
+use rand::{RngCore, SeedableRng};
+use rand::rngs::SmallRng;
+
+/// The game's one random number generator.
+///
+/// It is seeded once at boot and then stirred with whatever timing noise
+/// comes along, such as when keys are pressed, so two games never follow
+/// the same sequences even from the same boot seed.
+pub struct Rng {
+    inner: SmallRng,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self { Self { inner: SmallRng::seed_from_u64(seed) } }
+
+    /// Generator of round `round` of the challenge `seed`. Boards with the
+    /// same seed and rules play the same rounds, whatever the key timing.
+    pub fn for_round(seed: u64, round: u64) -> Self {
+        return Self::new(seed ^ round.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    }
+
+    /// Mixes `noise` into the state.
+    pub fn mix(&mut self, noise: u64) {
+        let state = self.inner.next_u64() ^ noise.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        self.inner = SmallRng::seed_from_u64(state);
+    }
+}
+
+impl RngCore for Rng {
+    fn next_u32(&mut self) -> u32 { return self.inner.next_u32(); }
+
+    fn next_u64(&mut self) -> u64 { return self.inner.next_u64(); }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) { self.inner.fill_bytes(dest); }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> { return self.inner.try_fill_bytes(dest); }
+}
@@ -0,0 +1,226 @@
+use rand::RngCore;
+use crate::{Mode, Rng, Rules, Score, Sequence, Staircase};
+
+/// How an answered round came out.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Verdict {
+    Correct,
+    Wrong,
+    /// The time of a timed round ran out.
+    TimedOut,
+}
+
+/// The rounds of a game and what carries from one to the next: the score,
+/// the chain Simon rounds grow, and the level adaptive rounds are played
+/// at, which also carries over from game to game.
+pub struct Rounds {
+    score: Score,
+    staircase: Staircase,
+    /// Sequence of the last round, which a Simon chain grows from.
+    last: Option<Sequence>,
+    /// Rounds of this game so far.
+    played: u64,
+}
+
+impl Rounds {
+    /// Adaptive rounds start at `difficulty`.
+    pub const fn new(difficulty: u8) -> Self { Self { score: Score::new(), staircase: Staircase::new(difficulty), last: None, played: 0 } }
+
+    pub fn score(&self) -> &Score { return &self.score; }
+
+    /// Level adaptive rounds are at.
+    pub fn level(&self) -> u8 { return self.staircase.level(); }
+
+    /// Starts adaptive rounds over at `difficulty`.
+    pub fn restart_level(&mut self, difficulty: u8) { self.staircase = Staircase::new(difficulty); }
+
+    /// Starts a new game; the best score and the level are kept.
+    pub fn new_game(&mut self) {
+        self.score.reset();
+        self.last = None;
+        self.played = 0;
+    }
+
+    /// Rules of the coming round: `rules`, at the staircase's level when
+    /// adaptive.
+    pub fn rules(&self, rules: &Rules) -> Rules {
+        let mut res = *rules;
+        if res.adaptive { res.difficulty = self.staircase.level(); }
+        return res;
+    }
+
+    /// Sequence of the coming round under `rules`, drawn from `rng`, or for
+    /// a `challenge` from `Rng::for_round` so that every board on the same
+    /// seed plays the same rounds. A Simon chain grows by one symbol a round
+    /// and starts over once it is `longest` or `MAX_LEN` long.
+    pub fn next_sequence(&mut self, rules: &Rules, challenge: Option<u64>, rng: &mut Rng, longest: usize) -> Sequence {
+        let mut rng = match challenge {
+            Some(seed) => Rng::for_round(seed, self.played),
+            None => Rng::new(rng.next_u64()),
+        };
+        self.played += 1;
+        let rules = self.rules(rules);
+        if let (Mode::Simon, Some(mut chain)) = (rules.mode, self.last) {
+            if chain.len() < longest && chain.grow(&rules, &mut rng) {
+                self.last = Some(chain);
+                return chain;
+            }
+        }
+        let sequence = Sequence::generate(&rules, &mut rng);
+        self.last = Some(sequence);
+        return sequence;
+    }
+
+    /// Judges `answer` to `showed` under `rules` and scores it. `answer` is
+    /// `None` when the time ran out; `left_ms` is the time a timed answer
+    /// had to spare and `latency_ms` how long it took.
+    ///
+    /// A classic round scores a point, plus the time bonus when timed, and
+    /// a Simon round the length of the chain. Adaptive rounds move the
+    /// staircase.
+    pub fn resolve(&mut self, rules: &Rules, showed: &Sequence, answer: Option<&[u8]>, left_ms: Option<u64>, latency_ms: u64) -> Verdict {
+        let rules = self.rules(rules);
+        let limit = rules.answer_ms(showed.len());
+        let Some(answer) = answer else {
+            if rules.adaptive { self.staircase.record(false, 0, 0); }
+            return Verdict::TimedOut;
+        };
+        let correct = showed.check(&rules, answer);
+        if rules.adaptive { self.staircase.record(correct, latency_ms, limit); }
+        if !correct { return Verdict::Wrong; }
+        match (rules.mode, left_ms) {
+            (Mode::Simon, _) => { self.score.reached(showed.len() as u64); }
+            (Mode::Classic, Some(left)) => { self.score.won_in(left, limit); }
+            (Mode::Classic, None) => { self.score.won(); }
+        }
+        return Verdict::Correct;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MAX_LEN, TIME_BONUS};
+
+    const NO_LIMIT: usize = MAX_LEN;
+
+    fn rules(mode: Mode, timed: bool, adaptive: bool) -> Rules {
+        return Rules { mode, timed, adaptive, ..Rules::new(5, true) };
+    }
+
+    /// Plays one round under `rules` and answers it with `answer`, or the
+    /// right answer.
+    fn play(rounds: &mut Rounds, rules: &Rules, answer: Option<&[u8]>, left_ms: Option<u64>) -> Verdict {
+        let showed = rounds.next_sequence(rules, None, &mut Rng::new(1), NO_LIMIT);
+        let expected = showed.expected(&rounds.rules(rules));
+        let answer = answer.unwrap_or(&expected[..showed.len()]);
+        return rounds.resolve(rules, &showed, Some(answer), left_ms, 0);
+    }
+
+    #[test]
+    fn classic_round_scores_a_point() {
+        let rules = rules(Mode::Classic, false, false);
+        let mut rounds = Rounds::new(rules.difficulty);
+        assert_eq!(play(&mut rounds, &rules, None, None), Verdict::Correct);
+        assert_eq!(play(&mut rounds, &rules, None, None), Verdict::Correct);
+        assert_eq!(rounds.score().points(), 2);
+    }
+
+    #[test]
+    fn timed_classic_round_earns_the_time_bonus() {
+        let rules = rules(Mode::Classic, true, false);
+        let limit = rules.answer_ms(rules.length());
+        let mut rounds = Rounds::new(rules.difficulty);
+        play(&mut rounds, &rules, None, Some(limit));
+        assert_eq!(rounds.score().points(), 1 + TIME_BONUS);
+        play(&mut rounds, &rules, None, Some(0));
+        assert_eq!(rounds.score().points(), 2 + TIME_BONUS);
+    }
+
+    #[test]
+    fn simon_round_scores_the_chain_length() {
+        let rules = rules(Mode::Simon, true, false);
+        let mut rounds = Rounds::new(rules.difficulty);
+        for _ in 0..3 { assert_eq!(play(&mut rounds, &rules, None, Some(1000)), Verdict::Correct); }
+        assert_eq!(rounds.score().points(), rules.length() as u64 + 2);
+    }
+
+    #[test]
+    fn wrong_answer_scores_nothing_and_steps_down() {
+        let rules = rules(Mode::Classic, false, true);
+        let mut rounds = Rounds::new(rules.difficulty);
+        assert_eq!(play(&mut rounds, &rules, Some(&[1]), None), Verdict::Wrong);
+        assert_eq!(rounds.score().points(), 0);
+        assert_eq!(rounds.level(), rules.difficulty - 1);
+    }
+
+    #[test]
+    fn fixed_rounds_leave_the_level_alone() {
+        let rules = rules(Mode::Classic, true, false);
+        let mut rounds = Rounds::new(rules.difficulty);
+        assert_eq!(play(&mut rounds, &rules, Some(&[1]), None), Verdict::Wrong);
+        let showed = rounds.next_sequence(&rules, None, &mut Rng::new(1), NO_LIMIT);
+        rounds.resolve(&rules, &showed, None, None, 0);
+        assert_eq!(rounds.level(), rules.difficulty);
+    }
+
+    #[test]
+    fn time_out_steps_down() {
+        let rules = rules(Mode::Classic, true, true);
+        let mut rounds = Rounds::new(rules.difficulty);
+        let showed = rounds.next_sequence(&rules, None, &mut Rng::new(1), NO_LIMIT);
+        assert_eq!(rounds.resolve(&rules, &showed, None, None, 0), Verdict::TimedOut);
+        assert_eq!(rounds.level(), rules.difficulty - 1);
+    }
+
+    #[test]
+    fn adaptive_rounds_are_played_at_the_level() {
+        let rules = rules(Mode::Classic, false, true);
+        let mut rounds = Rounds::new(9);
+        assert_eq!(rounds.rules(&rules).difficulty, 9);
+        let showed = rounds.next_sequence(&rules, None, &mut Rng::new(1), NO_LIMIT);
+        assert_eq!(showed.len(), Rules::new(9, true).length());
+        let fixed = Rules { adaptive: false, ..rules };
+        assert_eq!(rounds.rules(&fixed), fixed);
+    }
+
+    #[test]
+    fn simon_chain_grows_and_starts_over_at_the_limit() {
+        let rules = rules(Mode::Simon, false, false);
+        let mut rounds = Rounds::new(rules.difficulty);
+        let mut rng = Rng::new(1);
+        let (start, longest) = (rules.length(), rules.length() + 1);
+        let first = rounds.next_sequence(&rules, None, &mut rng, longest);
+        assert_eq!(first.len(), start);
+        let grown = rounds.next_sequence(&rules, None, &mut rng, longest);
+        assert_eq!(grown.len(), longest);
+        assert_eq!(grown.flashes()[..start], first.flashes()[..]);
+        assert_eq!(rounds.next_sequence(&rules, None, &mut rng, longest).len(), start);
+    }
+
+    #[test]
+    fn new_game_starts_the_chain_over() {
+        let rules = rules(Mode::Simon, false, false);
+        let mut rounds = Rounds::new(rules.difficulty);
+        let mut rng = Rng::new(1);
+        rounds.next_sequence(&rules, None, &mut rng, NO_LIMIT);
+        rounds.new_game();
+        assert_eq!(rounds.next_sequence(&rules, None, &mut rng, NO_LIMIT).len(), rules.length());
+    }
+
+    #[test]
+    fn challenge_rounds_follow_the_seed_alone() {
+        let rules = rules(Mode::Classic, false, false);
+        let (mut a, mut b) = (Rounds::new(rules.difficulty), Rounds::new(rules.difficulty));
+        let (mut rng_a, mut rng_b) = (Rng::new(1), Rng::new(2));
+        for _ in 0..3 {
+            let showed = a.next_sequence(&rules, Some(42), &mut rng_a, NO_LIMIT);
+            assert_eq!(b.next_sequence(&rules, Some(42), &mut rng_b, NO_LIMIT), showed);
+        }
+        let third = a.next_sequence(&rules, Some(42), &mut rng_a, NO_LIMIT);
+        a.new_game();
+        let first = a.next_sequence(&rules, Some(42), &mut rng_a, NO_LIMIT);
+        assert_eq!(first, Rounds::new(rules.difficulty).next_sequence(&rules, Some(42), &mut rng_b, NO_LIMIT));
+        assert_ne!(first, third);
+    }
+}
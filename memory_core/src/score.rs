@@ -0,0 +1,233 @@
+use crate::{Mode, Record, Rules};
+
+/// Points of the current game, and the best game since power-up.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Score {
+    points: u64,
+    best: u64,
+}
+
+impl Score {
+    pub const fn new() -> Self { Self { points: 0, best: 0 } }
+
+    pub fn points(&self) -> u64 { return self.points; }
+
+    pub fn best(&self) -> u64 { return self.best; }
+
+    /// Counts a won round.
+    pub fn won(&mut self) {
+        self.points += 1;
+        if self.points > self.best { self.best = self.points; }
+    }
+
+    /// Counts a round won with `left_ms` of `limit_ms` to spare: one
+    /// point, plus up to `TIME_BONUS` for the time left.
+    pub fn won_in(&mut self, left_ms: u64, limit_ms: u64) {
+        self.points += 1 + TIME_BONUS * left_ms.min(limit_ms) / limit_ms.max(1);
+        if self.points > self.best { self.best = self.points; }
+    }
+
+    /// Counts a Simon chain of `len` symbols reproduced; the points are the
+    /// longest chain.
+    pub fn reached(&mut self, len: u64) {
+        self.points = self.points.max(len);
+        if self.points > self.best { self.best = self.points; }
+    }
+
+    /// Starts a new game; the best run is kept.
+    pub fn reset(&mut self) { self.points = 0; }
+}
+
+/// Most extra points a timed round can earn.
+pub const TIME_BONUS: u64 = 4;
+
+/// How the points of a game are counted. Scores only compare within one
+/// scheme, so each has its own high-score table.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub enum Scoring {
+    /// A point a classic round.
+    #[default]
+    Classic,
+    /// A point a classic round, plus the time bonus.
+    Timed,
+    /// The longest Simon chain, timed or not.
+    Simon,
+}
+
+impl Scoring {
+    pub const COUNT: usize = 3;
+
+    /// How games under `rules` are scored.
+    pub fn of(rules: &Rules) -> Scoring {
+        match (rules.mode, rules.timed) {
+            (Mode::Simon, _) => Scoring::Simon,
+            (Mode::Classic, true) => Scoring::Timed,
+            (Mode::Classic, false) => Scoring::Classic,
+        }
+    }
+
+    pub fn index(self) -> usize { return self as usize; }
+
+    pub fn from_index(index: u8) -> Option<Scoring> {
+        match index {
+            0 => Some(Scoring::Classic),
+            1 => Some(Scoring::Timed),
+            2 => Some(Scoring::Simon),
+            _ => None,
+        }
+    }
+}
+
+/// Record kind of a `HighScore` in the `Store`.
+pub const HIGH_SCORE: u8 = 1;
+
+/// One line of a high-score table. Initials are ASCII, `b' '` where the
+/// player left them out.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct HighScore {
+    /// Table the score belongs in.
+    pub scoring: Scoring,
+    pub score: u32,
+    pub initials: [u8; 3],
+}
+
+impl HighScore {
+    pub const fn new(scoring: Scoring, score: u32, initials: [u8; 3]) -> Self { Self { scoring, score, initials } }
+
+    pub(crate) fn to_record(self) -> Record {
+        let mut payload = [0; 8];
+        payload[..4].copy_from_slice(&self.score.to_le_bytes());
+        payload[4..7].copy_from_slice(&self.initials);
+        payload[7] = self.scoring as u8;
+        return Record::new(HIGH_SCORE, payload);
+    }
+
+    /// `None` for a scheme this firmware does not know.
+    pub(crate) fn from_record(record: &Record) -> Option<HighScore> {
+        let p = record.payload;
+        let scoring = Scoring::from_index(p[7])?;
+        return Some(HighScore { scoring, score: u32::from_le_bytes([p[0], p[1], p[2], p[3]]), initials: [p[4], p[5], p[6]] });
+    }
+}
+
+impl Default for HighScore {
+    fn default() -> Self { Self::new(Scoring::Classic, 0, [b' '; 3]) }
+}
+
+/// The best `N` scores of one `Scoring`, best first. Equal scores keep the order they were
+/// made in.
+pub struct HighScores<const N: usize> {
+    entries: [HighScore; N],
+    len: usize,
+}
+
+impl<const N: usize> HighScores<N> {
+    pub const fn new() -> Self { Self { entries: [HighScore::new(Scoring::Classic, 0, [b' '; 3]); N], len: 0 } }
+
+    pub fn entries(&self) -> &[HighScore] { return &self.entries[..self.len]; }
+
+    pub fn len(&self) -> usize { return self.len; }
+
+    pub fn is_empty(&self) -> bool { return self.len == 0; }
+
+    pub fn best(&self) -> Option<HighScore> { return self.entries().first().copied(); }
+
+    /// Whether `score` would make it into the table.
+    pub fn qualifies(&self, score: u32) -> bool {
+        if score == 0 { return false; }
+        return self.len < N || score > self.entries[N - 1].score;
+    }
+
+    /// Puts `entry` in its place; its rank from 0, or `None` if it did not
+    /// make it.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        if !self.qualifies(entry.score) { return None; }
+        let mut at = self.len.min(N - 1);
+        if self.len < N { self.len += 1; }
+        while at > 0 && self.entries[at - 1].score < entry.score {
+            self.entries[at] = self.entries[at - 1];
+            at -= 1;
+        }
+        self.entries[at] = entry;
+        return Some(at);
+    }
+}
+
+impl<const N: usize> Default for HighScores<N> {
+    fn default() -> Self { Self::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn best_survives_reset() {
+        let mut score = Score::new();
+        score.won();
+        score.won();
+        score.reset();
+        score.won();
+        assert_eq!((score.points(), score.best()), (1, 2));
+    }
+
+    #[test]
+    fn time_bonus_scales_with_time_left() {
+        let mut score = Score::new();
+        score.won_in(0, 1000);
+        assert_eq!(score.points(), 1);
+        score.won_in(500, 1000);
+        assert_eq!(score.points(), 1 + 1 + TIME_BONUS / 2);
+        score.reset();
+        score.won_in(5000, 1000);
+        assert_eq!(score.points(), 1 + TIME_BONUS);
+    }
+
+    #[test]
+    fn simon_keeps_the_longest_chain() {
+        let mut score = Score::new();
+        score.reached(5);
+        score.reached(3);
+        assert_eq!(score.points(), 5);
+    }
+
+    #[test]
+    fn insert_keeps_best_first_and_ties_in_order() {
+        let mut table: HighScores<3> = HighScores::new();
+        assert_eq!(table.insert(HighScore::new(Scoring::Classic, 5, *b"aaa")), Some(0));
+        assert_eq!(table.insert(HighScore::new(Scoring::Classic, 9, *b"bbb")), Some(0));
+        assert_eq!(table.insert(HighScore::new(Scoring::Classic, 5, *b"ccc")), Some(2));
+        let initials: [[u8; 3]; 3] = core::array::from_fn(|i| table.entries()[i].initials);
+        assert_eq!(initials, [*b"bbb", *b"aaa", *b"ccc"]);
+    }
+
+    #[test]
+    fn full_table_drops_the_lowest() {
+        let mut table: HighScores<3> = HighScores::new();
+        for score in [4, 6, 8] { table.insert(HighScore::new(Scoring::Classic, score, *b"   ")); }
+        assert!(!table.qualifies(4));
+        assert_eq!(table.insert(HighScore::new(Scoring::Classic, 4, *b"   ")), None);
+        assert_eq!(table.insert(HighScore::new(Scoring::Classic, 0, *b"   ")), None);
+        assert_eq!(table.insert(HighScore::new(Scoring::Classic, 7, *b"   ")), Some(1));
+        let scores: [u32; 3] = core::array::from_fn(|i| table.entries()[i].score);
+        assert_eq!(scores, [8, 7, 6]);
+        assert_eq!(table.best(), Some(HighScore::new(Scoring::Classic, 8, *b"   ")));
+    }
+
+    #[test]
+    fn scoring_follows_the_mode_and_the_timer() {
+        let classic = Rules { mode: Mode::Classic, timed: false, ..Rules::default() };
+        assert_eq!(Scoring::of(&classic), Scoring::Classic);
+        assert_eq!(Scoring::of(&Rules { timed: true, ..classic }), Scoring::Timed);
+        assert_eq!(Scoring::of(&Rules { mode: Mode::Simon, timed: true, ..classic }), Scoring::Simon);
+    }
+
+    #[test]
+    fn record_keeps_the_scoring() {
+        let entry = HighScore::new(Scoring::Simon, 9, *b"abc");
+        assert_eq!(HighScore::from_record(&entry.to_record()), Some(entry));
+        let mut unknown = entry.to_record();
+        unknown.payload[7] = Scoring::COUNT as u8;
+        assert_eq!(HighScore::from_record(&unknown), None);
+    }
+}
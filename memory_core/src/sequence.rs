@@ -0,0 +1,157 @@
+use rand::{Rng as _, RngCore};
+use crate::{Recall, Rules, SYMBOLS};
+
+/// Longest sequence. Classic rounds reach 10 at `MAX_DIFFICULTY`; Simon
+/// chains stop growing here.
+pub const MAX_LEN: usize = 16;
+
+/// One symbol of a sequence and the cell it is shown in.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Flash {
+    /// `1..=Rules::symbols()`.
+    pub symbol: u8,
+    /// `0..SYMBOLS`.
+    pub cell: u8,
+}
+
+/// What the player has to remember in one round.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Sequence {
+    flashes: [Flash; MAX_LEN],
+    len: usize,
+}
+
+impl Sequence {
+    /// Sequence for `rules`, drawn from `rng`. Cells are drawn after each
+    /// symbol unless `rules.fixed`.
+    pub fn generate(rules: &Rules, rng: &mut impl RngCore) -> Self {
+        let mut res = Sequence { flashes: [Flash::default(); MAX_LEN], len: rules.length().min(MAX_LEN) };
+        for i in 0..res.len { res.flashes[i] = Self::flash(rules, rng); }
+        return res;
+    }
+
+    /// Adds one symbol at the end; `false` if the sequence is at `MAX_LEN`.
+    pub fn grow(&mut self, rules: &Rules, rng: &mut impl RngCore) -> bool {
+        if self.len >= MAX_LEN { return false; }
+        self.flashes[self.len] = Self::flash(rules, rng);
+        self.len += 1;
+        return true;
+    }
+
+    fn flash(rules: &Rules, rng: &mut impl RngCore) -> Flash {
+        let symbol = rng.gen_range(1..=rules.symbols());
+        let cell = if rules.fixed { symbol - 1 } else { rng.gen_range(0..SYMBOLS) };
+        return Flash { symbol, cell };
+    }
+
+    pub fn flashes(&self) -> &[Flash] { return &self.flashes[..self.len]; }
+
+    pub fn len(&self) -> usize { return self.len; }
+
+    pub fn is_empty(&self) -> bool { return self.len == 0; }
+
+    /// Symbol `index` of the sequence.
+    pub fn symbol(&self, index: usize) -> Option<u8> {
+        return self.flashes().get(index).map(|f| f.symbol);
+    }
+
+    /// Answer expected under `rules`; the first `len()` symbols are used.
+    pub fn expected(&self, rules: &Rules) -> [u8; MAX_LEN] {
+        let mut res = [0; MAX_LEN];
+        for (i, flash) in self.flashes().iter().enumerate() {
+            res[i] = if rules.recall == Recall::Position { flash.cell + 1 } else { flash.symbol };
+        }
+        rules.recall.apply(&mut res[..self.len], rules.symbols());
+        return res;
+    }
+
+    /// Whether `answer` is the sequence as `rules` want it entered.
+    pub fn check(&self, rules: &Rules, answer: &[u8]) -> bool {
+        return answer == &self.expected(rules)[..self.len];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Rng, MAX_DIFFICULTY};
+
+    /// Sequence of `(symbol, cell)` pairs.
+    fn sequence(flashes: &[(u8, u8)]) -> Sequence {
+        let mut res = Sequence { flashes: [Flash::default(); MAX_LEN], len: flashes.len() };
+        for (i, (symbol, cell)) in flashes.iter().enumerate() { res.flashes[i] = Flash { symbol: *symbol, cell: *cell }; }
+        return res;
+    }
+
+    #[test]
+    fn fixed_cells_follow_the_symbol() {
+        let rules = Rules::new(MAX_DIFFICULTY, true);
+        let mut rng = Rng::new(1);
+        for _ in 0..50 {
+            let sequence = Sequence::generate(&rules, &mut rng);
+            assert_eq!(sequence.len(), rules.length());
+            for flash in sequence.flashes() {
+                assert!((1..=rules.symbols()).contains(&flash.symbol));
+                assert_eq!(flash.cell, flash.symbol - 1);
+            }
+        }
+    }
+
+    #[test]
+    fn random_cells_are_drawn_apart_from_the_symbol() {
+        let rules = Rules::new(MAX_DIFFICULTY, false);
+        let mut rng = Rng::new(1);
+        let mut moved = 0;
+        for _ in 0..50 {
+            let sequence = Sequence::generate(&rules, &mut rng);
+            for flash in sequence.flashes() {
+                assert!(flash.cell < SYMBOLS);
+                if flash.cell != flash.symbol - 1 { moved += 1; }
+            }
+        }
+        assert!(moved > 0);
+    }
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let rules = Rules::new(5, false);
+        assert_eq!(Sequence::generate(&rules, &mut Rng::new(7)), Sequence::generate(&rules, &mut Rng::new(7)));
+    }
+
+    #[test]
+    fn grow_stops_at_max_len() {
+        let rules = Rules::default();
+        let mut rng = Rng::new(1);
+        let mut sequence = Sequence::generate(&rules, &mut rng);
+        let first = sequence.flashes()[0];
+        while sequence.grow(&rules, &mut rng) {}
+        assert_eq!(sequence.len(), MAX_LEN);
+        assert_eq!(sequence.flashes()[0], first);
+    }
+
+    #[test]
+    fn check_each_recall() {
+        let shown = sequence(&[(3, 7), (16, 0), (2, 5)]);
+        let cases: [(Recall, [u8; 3]); 5] = [
+            (Recall::Forward, [3, 16, 2]),
+            (Recall::Reverse, [2, 16, 3]),
+            (Recall::Sorted, [2, 3, 16]),
+            (Recall::PlusOne, [4, 1, 3]),
+            (Recall::Position, [8, 1, 6]),
+        ];
+        for (recall, answer) in cases {
+            let rules = Rules { recall, ..Rules::new(MAX_DIFFICULTY, false) };
+            assert!(shown.check(&rules, &answer), "{:?}", recall);
+            assert!(!shown.check(&rules, &answer[..2]), "{:?}", recall);
+            assert!(!shown.check(&rules, &[answer[0], answer[1], answer[2], 1]), "{:?}", recall);
+            assert!(!shown.check(&rules, &[answer[1], answer[0], answer[2]]), "{:?}", recall);
+        }
+    }
+
+    #[test]
+    fn plus_one_wraps_within_the_adaptive_symbols() {
+        let rules = Rules { recall: Recall::PlusOne, adaptive: true, ..Rules::new(1, true) };
+        assert_eq!(rules.symbols(), 4);
+        assert!(sequence(&[(4, 3), (1, 0)]).check(&rules, &[1, 2]));
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{Mode, Recall, Record, Rules, MAX_DIFFICULTY, MIN_DIFFICULTY};
+
+/// Record kind of `Settings` in the `Store`.
+pub const SETTINGS: u8 = 2;
+/// Layout `Settings` are written in.
+pub const SETTINGS_VERSION: u8 = 1;
+/// Highest display brightness.
+pub const MAX_BRIGHTNESS: u8 = 7;
+
+/// What the settings screen edits.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Settings {
+    pub rules: Rules,
+    /// `0..=MAX_BRIGHTNESS`.
+    pub brightness: u8,
+    pub b_skin: bool,
+    pub d_skin: bool,
+}
+
+impl Settings {
+    pub(crate) fn to_record(self) -> Record {
+        let payload = [
+            SETTINGS_VERSION,
+            self.rules.difficulty,
+            self.brightness,
+            self.rules.fixed as u8 | (self.rules.timed as u8) << 1 | (self.rules.adaptive as u8) << 2,
+            self.b_skin as u8,
+            self.d_skin as u8,
+            self.rules.mode.index(),
+            self.rules.recall.index(),
+        ];
+        return Record::new(SETTINGS, payload);
+    }
+
+    /// `None` for other versions and for values out of range, which are
+    /// better replaced by the defaults.
+    pub(crate) fn from_record(record: &Record) -> Option<Settings> {
+        let p = record.payload;
+        if p[0] != SETTINGS_VERSION { return None; }
+        if p[1] < MIN_DIFFICULTY || p[1] > MAX_DIFFICULTY || p[2] > MAX_BRIGHTNESS || p[3] > 0b111 { return None; }
+        let mut rules = Rules::new(p[1], p[3] & 1 != 0);
+        rules.timed = p[3] & 0b10 != 0;
+        rules.adaptive = p[3] & 0b100 != 0;
+        rules.mode = Mode::from_index(p[6])?;
+        rules.recall = Recall::from_index(p[7])?;
+        return Some(Settings { rules, brightness: p[2], b_skin: Self::flag(p[4])?, d_skin: Self::flag(p[5])? });
+    }
+
+    fn flag(byte: u8) -> Option<bool> {
+        match byte {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self { Self { rules: Rules::default(), brightness: 4, b_skin: false, d_skin: false } }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{crc32, RamFlash, Store, RECORD_SIZE};
+
+    fn changed() -> Settings {
+        let mut rules = Rules::new(9, false);
+        rules.mode = Mode::Simon;
+        rules.recall = Recall::Position;
+        rules.timed = true;
+        rules.adaptive = true;
+        return Settings { rules, brightness: 7, b_skin: true, d_skin: false };
+    }
+
+    #[test]
+    fn record_round_trips() {
+        let settings = changed();
+        assert_eq!(Settings::from_record(&settings.to_record()), Some(settings));
+        assert_eq!(Settings::from_record(&Settings::default().to_record()), Some(Settings::default()));
+    }
+
+    /// Saves `changed()` to a fresh store, lets `corrupt` change the bytes of
+    /// its record, which comes first, and loads it back.
+    fn load_corrupted(corrupt: impl FnOnce(&mut [u8])) -> Settings {
+        let mut store: Store<RamFlash<512>, 3> = Store::new(RamFlash::new());
+        store.save_settings(changed()).unwrap();
+        let mut flash = RamFlash::<512>::new();
+        *flash.bytes_mut() = *store.storage().bytes();
+        corrupt(&mut flash.bytes_mut()[..RECORD_SIZE as usize]);
+        let mut store: Store<RamFlash<512>, 3> = Store::new(flash);
+        store.load().unwrap();
+        return store.settings();
+    }
+
+    /// Sets payload byte `index` to `value` and fixes the CRC, as if the
+    /// record had been written that way.
+    fn set_payload(record: &mut [u8], index: usize, value: u8) {
+        record[4 + index] = value;
+        let crc = crc32(&record[..12]);
+        record[12..].copy_from_slice(&crc.to_le_bytes());
+    }
+
+    #[test]
+    fn saved_settings_load_back() {
+        assert_eq!(load_corrupted(|_| {}), changed());
+    }
+
+    #[test]
+    fn unknown_version_falls_back_to_defaults() {
+        for version in [0, SETTINGS_VERSION + 1, 0xff] {
+            assert_eq!(load_corrupted(|record| set_payload(record, 0, version)), Settings::default());
+        }
+    }
+
+    #[test]
+    fn value_out_of_range_falls_back_to_defaults() {
+        let cases = [(1, 0), (1, MAX_DIFFICULTY + 1), (2, MAX_BRIGHTNESS + 1), (3, 0b1000), (4, 2), (5, 2), (6, Mode::COUNT), (7, Recall::COUNT)];
+        for (index, value) in cases {
+            assert_eq!(load_corrupted(|record| set_payload(record, index, value)), Settings::default(), "byte {} = {}", index, value);
+        }
+    }
+
+    #[test]
+    fn bad_crc_falls_back_to_defaults() {
+        assert_eq!(load_corrupted(|record| record[6] ^= 0x10), Settings::default());
+        assert_eq!(load_corrupted(|record| record[14] ^= 0x01), Settings::default());
+    }
+}
@@ -0,0 +1,77 @@
+use crate::{MAX_DIFFICULTY, MIN_DIFFICULTY};
+
+/// Credit needed to step up a level.
+const STEP_UP: u8 = 4;
+
+/// Difficulty that follows how the player does, for `Rules::adaptive`.
+///
+/// A weighted up/down staircase: a miss steps down a level at once, while
+/// wins earn credit, two for an answer in under half the time allowed and
+/// one for a slower one, and four credits step up. Two quick wins or four
+/// slow ones in a row therefore go up a level.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Staircase {
+    level: u8,
+    credit: u8,
+}
+
+impl Staircase {
+    pub const fn new(level: u8) -> Self { Self { level, credit: 0 } }
+
+    /// Difficulty to play the next round at.
+    pub fn level(&self) -> u8 { return self.level; }
+
+    /// Counts a round; `latency_ms` is how long the answer took out of the
+    /// `limit_ms` it was allowed.
+    pub fn record(&mut self, correct: bool, latency_ms: u64, limit_ms: u64) {
+        if !correct {
+            self.level = self.level.saturating_sub(1).max(MIN_DIFFICULTY);
+            self.credit = 0;
+            return;
+        }
+        self.credit += if latency_ms * 2 < limit_ms { 2 } else { 1 };
+        if self.credit >= STEP_UP {
+            self.level = (self.level + 1).min(MAX_DIFFICULTY);
+            self.credit = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_quick_or_four_slow_wins_step_up() {
+        let mut quick = Staircase::new(5);
+        quick.record(true, 100, 1000);
+        assert_eq!(quick.level(), 5);
+        quick.record(true, 499, 1000);
+        assert_eq!(quick.level(), 6);
+        let mut slow = Staircase::new(5);
+        for _ in 0..3 { slow.record(true, 500, 1000); }
+        assert_eq!(slow.level(), 5);
+        slow.record(true, 1000, 1000);
+        assert_eq!(slow.level(), 6);
+    }
+
+    #[test]
+    fn a_miss_steps_down_and_clears_the_credit() {
+        let mut staircase = Staircase::new(5);
+        staircase.record(true, 100, 1000);
+        staircase.record(false, 100, 1000);
+        assert_eq!(staircase.level(), 4);
+        staircase.record(true, 100, 1000);
+        assert_eq!(staircase.level(), 4);
+    }
+
+    #[test]
+    fn level_stays_in_range() {
+        let mut low = Staircase::new(MIN_DIFFICULTY);
+        low.record(false, 0, 1000);
+        assert_eq!(low.level(), MIN_DIFFICULTY);
+        let mut high = Staircase::new(MAX_DIFFICULTY);
+        for _ in 0..2 { high.record(true, 0, 1000); }
+        assert_eq!(high.level(), MAX_DIFFICULTY);
+    }
+}
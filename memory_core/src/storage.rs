@@ -0,0 +1,158 @@
+use crate::crc::crc32;
+
+/// NOR-flash-like memory the game keeps its records in.
+///
+/// Erased bytes read as `0xff`; writing can only clear bits, so a byte is
+/// written once between erases. Offsets are relative to the start of the
+/// storage.
+pub trait Storage {
+    type Error;
+
+    /// Size in bytes.
+    fn capacity(&self) -> u32;
+
+    fn read(&mut self, offset: u32, buf: &mut [u8]) -> Result<(), Self::Error>;
+
+    fn write(&mut self, offset: u32, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Erases the `len` bytes from `offset`, which must be whole erase
+    /// sectors.
+    fn erase(&mut self, offset: u32, len: u32) -> Result<(), Self::Error>;
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct OutOfBounds;
+
+/// `Storage` in RAM, with the same write-only-clears-bits behaviour as
+/// flash, for running the game logic on the host.
+pub struct RamFlash<const N: usize> {
+    bytes: [u8; N],
+}
+
+impl<const N: usize> RamFlash<N> {
+    pub const fn new() -> Self { Self { bytes: [0xff; N] } }
+
+    pub fn bytes(&self) -> &[u8; N] { return &self.bytes; }
+
+    /// Direct access, to fake torn writes and bit rot.
+    pub fn bytes_mut(&mut self) -> &mut [u8; N] { return &mut self.bytes; }
+}
+
+impl<const N: usize> Default for RamFlash<N> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<const N: usize> Storage for RamFlash<N> {
+    type Error = OutOfBounds;
+
+    fn capacity(&self) -> u32 { return N as u32; }
+
+    fn read(&mut self, offset: u32, buf: &mut [u8]) -> Result<(), OutOfBounds> {
+        let start = offset as usize;
+        let src = self.bytes.get(start..start + buf.len()).ok_or(OutOfBounds)?;
+        buf.copy_from_slice(src);
+        return Ok(());
+    }
+
+    fn write(&mut self, offset: u32, data: &[u8]) -> Result<(), OutOfBounds> {
+        let start = offset as usize;
+        let dst = self.bytes.get_mut(start..start + data.len()).ok_or(OutOfBounds)?;
+        for (byte, new) in dst.iter_mut().zip(data) { *byte &= *new; }
+        return Ok(());
+    }
+
+    fn erase(&mut self, offset: u32, len: u32) -> Result<(), OutOfBounds> {
+        let start = offset as usize;
+        let dst = self.bytes.get_mut(start..start + len as usize).ok_or(OutOfBounds)?;
+        dst.fill(0xff);
+        return Ok(());
+    }
+}
+
+/// Bytes of a record in storage, a multiple of the flash write size.
+pub const RECORD_SIZE: u32 = 16;
+const MAGIC: u8 = 0xa5;
+
+/// Fixed-size entry of a `Log`. `kind` tells the owners of a shared log
+/// apart; what `payload` holds is up to them.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Record {
+    pub kind: u8,
+    pub payload: [u8; 8],
+}
+
+impl Record {
+    pub const fn new(kind: u8, payload: [u8; 8]) -> Self { Self { kind, payload } }
+
+    fn encode(&self) -> [u8; RECORD_SIZE as usize] {
+        let mut res = [0; RECORD_SIZE as usize];
+        res[0] = MAGIC;
+        res[1] = self.kind;
+        res[4..12].copy_from_slice(&self.payload);
+        let crc = crc32(&res[..12]);
+        res[12..].copy_from_slice(&crc.to_le_bytes());
+        return res;
+    }
+
+    /// `None` for erased slots and for slots whose write was torn.
+    fn decode(bytes: &[u8; RECORD_SIZE as usize]) -> Option<Record> {
+        if bytes[0] != MAGIC { return None; }
+        let crc = u32::from_le_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]);
+        if crc32(&bytes[..12]) != crc { return None; }
+        let mut payload = [0; 8];
+        payload.copy_from_slice(&bytes[4..12]);
+        return Some(Record { kind: bytes[1], payload });
+    }
+}
+
+/// Append-only list of records in one area of a `Storage`.
+///
+/// Each change is a new record after the last one, so every byte is
+/// written once per erase. A record is checked against its CRC when read,
+/// and one that was cut short by a power loss is skipped. When the area is
+/// full its owner moves the records still live elsewhere and `clear`s it.
+pub struct Log {
+    start: u32,
+    size: u32,
+    /// Offset of the first free slot from `start`, once known.
+    end: Option<u32>,
+}
+
+impl Log {
+    /// Log over the `size` bytes from `start`.
+    pub const fn new(start: u32, size: u32) -> Self { Self { start, size, end: None } }
+
+    /// Calls `f` with every valid record, oldest first.
+    pub fn for_each<S: Storage>(&mut self, storage: &mut S, mut f: impl FnMut(Record)) -> Result<(), S::Error> {
+        let mut offset = 0;
+        let mut bytes = [0; RECORD_SIZE as usize];
+        while offset + RECORD_SIZE <= self.size {
+            storage.read(self.start + offset, &mut bytes)?;
+            if bytes.iter().all(|b| *b == 0xff) { break; }
+            if let Some(record) = Record::decode(&bytes) { f(record); }
+            offset += RECORD_SIZE;
+        }
+        self.end = Some(offset);
+        return Ok(());
+    }
+
+    /// Adds `record` after the last one; `false` if there is no room left.
+    pub fn append<S: Storage>(&mut self, storage: &mut S, record: &Record) -> Result<bool, S::Error> {
+        let end = match self.end {
+            Some(end) => end,
+            None => { self.for_each(storage, |_| {})?; self.end.unwrap_or(0) }
+        };
+        if end + RECORD_SIZE > self.size { return Ok(false); }
+        self.end = Some(end + RECORD_SIZE);
+        storage.write(self.start + end, &record.encode())?;
+        return Ok(true);
+    }
+
+    /// Erases the area, leaving the log empty.
+    pub fn clear<S: Storage>(&mut self, storage: &mut S) -> Result<(), S::Error> {
+        self.end = None;
+        storage.erase(self.start, self.size)?;
+        self.end = Some(0);
+        return Ok(());
+    }
+}
@@ -0,0 +1,284 @@
+use crate::{HighScore, HighScores, Log, Record, Scoring, Settings, Storage, HIGH_SCORE, SETTINGS};
+
+/// Record kind that closes a compaction, with the generation of its area
+/// in the first four payload bytes.
+const AREA: u8 = 0;
+
+/// Everything the game keeps across power cycles, in two `Log` areas, each
+/// half of the storage.
+///
+/// Records are only ever appended to the area in use; the last valid
+/// `Settings` record wins and every `HighScore` record is replayed into the
+/// table of its `Scoring`. When the area is full, the tables and the
+/// settings are written to
+/// the other one, followed by an `AREA` record with the next generation.
+/// Only a complete area has that record, and `load` takes the newest
+/// complete one, so a power loss during the move leaves the old area in
+/// use.
+pub struct Store<S: Storage, const N: usize> {
+    storage: S,
+    areas: [Log; 2],
+    /// Area in use and its generation; `None` until the first save.
+    active: Option<(usize, u32)>,
+    /// A table per `Scoring`, by index.
+    high_scores: [HighScores<N>; Scoring::COUNT],
+    settings: Settings,
+}
+
+impl<S: Storage, const N: usize> Store<S, N> {
+    /// Store with defaults; call `load` to read what `storage` holds. Each
+    /// half of `storage` must be whole erase sectors.
+    pub fn new(storage: S) -> Self {
+        let half = storage.capacity() / 2;
+        Self { storage, areas: [Log::new(0, half), Log::new(half, half)], active: None, high_scores: [const { HighScores::new() }; Scoring::COUNT], settings: Settings::default() }
+    }
+
+    pub fn storage(&mut self) -> &mut S { return &mut self.storage; }
+
+    pub fn load(&mut self) -> Result<(), S::Error> {
+        let mut active: Option<(usize, u32)> = None;
+        for (i, area) in self.areas.iter_mut().enumerate() {
+            let mut generation = None;
+            area.for_each(&mut self.storage, |record| {
+                if record.kind == AREA { generation = Some(u32::from_le_bytes([record.payload[0], record.payload[1], record.payload[2], record.payload[3]])); }
+            })?;
+            if let Some(generation) = generation {
+                if active.is_none_or(|(_, newest)| generation > newest) { active = Some((i, generation)); }
+            }
+        }
+        let mut high_scores = [const { HighScores::new() }; Scoring::COUNT];
+        let mut settings = Settings::default();
+        if let Some((area, _)) = active {
+            self.areas[area].for_each(&mut self.storage, |record| {
+                match record.kind {
+                    HIGH_SCORE => {
+                        if let Some(entry) = HighScore::from_record(&record) { high_scores[entry.scoring.index()].insert(entry); }
+                    }
+                    SETTINGS => { settings = Settings::from_record(&record).unwrap_or_default(); }
+                    _ => {}
+                }
+            })?;
+        }
+        self.active = active;
+        self.high_scores = high_scores;
+        self.settings = settings;
+        return Ok(());
+    }
+
+    pub fn high_scores(&self, scoring: Scoring) -> &HighScores<N> { return &self.high_scores[scoring.index()]; }
+
+    pub fn settings(&self) -> Settings { return self.settings; }
+
+    /// Puts `entry` in the table of its scoring and saves it if it made it;
+    /// its rank from 0.
+    pub fn add_score(&mut self, entry: HighScore) -> Result<Option<usize>, S::Error> {
+        let Some(rank) = self.high_scores[entry.scoring.index()].insert(entry) else { return Ok(None); };
+        self.append(&entry.to_record())?;
+        return Ok(Some(rank));
+    }
+
+    /// Saves `settings` unless they are what is saved already.
+    pub fn save_settings(&mut self, settings: Settings) -> Result<(), S::Error> {
+        if settings == self.settings { return Ok(()); }
+        self.settings = settings;
+        return self.append(&settings.to_record());
+    }
+
+    fn append(&mut self, record: &Record) -> Result<(), S::Error> {
+        let Some((area, _)) = self.active else { return self.compact(); };
+        if self.areas[area].append(&mut self.storage, record)? { return Ok(()); }
+        return self.compact();
+    }
+
+    /// Moves the tables and the settings to the other area; the first save
+    /// starts the first area this way.
+    fn compact(&mut self) -> Result<(), S::Error> {
+        let (area, generation) = match self.active {
+            Some((area, generation)) => (1 - area, generation.wrapping_add(1)),
+            None => (0, 1),
+        };
+        let log = &mut self.areas[area];
+        log.clear(&mut self.storage)?;
+        for entry in self.high_scores.iter().flat_map(|table| table.entries()) { log.append(&mut self.storage, &entry.to_record())?; }
+        log.append(&mut self.storage, &self.settings.to_record())?;
+        let mut payload = [0; 8];
+        payload[..4].copy_from_slice(&generation.to_le_bytes());
+        log.append(&mut self.storage, &Record::new(AREA, payload))?;
+        self.active = Some((area, generation));
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OutOfBounds, RamFlash, RECORD_SIZE};
+
+    /// Two areas of 8 records.
+    const BYTES: usize = 2 * 8 * RECORD_SIZE as usize;
+
+    type Flash = RamFlash<BYTES>;
+
+    fn settings(difficulty: u8) -> Settings {
+        let mut res = Settings::default();
+        res.rules.difficulty = difficulty;
+        return res;
+    }
+
+    fn scores<const N: usize>(store: &Store<impl Storage, N>) -> [u32; N] {
+        let entries = store.high_scores(Scoring::Classic).entries();
+        return core::array::from_fn(|i| entries.get(i).map_or(0, |e| e.score));
+    }
+
+    /// What a fresh boot reads back from `flash`.
+    fn reload(flash: &Flash) -> Store<Flash, 3> {
+        let mut copy = Flash::new();
+        *copy.bytes_mut() = *flash.bytes();
+        let mut store = Store::new(copy);
+        store.load().unwrap();
+        return store;
+    }
+
+    #[test]
+    fn blank_storage_loads_defaults() {
+        let store = reload(&Flash::new());
+        assert_eq!(store.settings(), Settings::default());
+        assert!(store.high_scores(Scoring::Classic).is_empty());
+    }
+
+    #[test]
+    fn load_after_append() {
+        let mut store: Store<Flash, 3> = Store::new(Flash::new());
+        store.load().unwrap();
+        store.save_settings(settings(7)).unwrap();
+        store.add_score(HighScore::new(Scoring::Classic, 12, *b"abc")).unwrap();
+        store.add_score(HighScore::new(Scoring::Classic, 30, *b"xyz")).unwrap();
+        let loaded = reload(store.storage());
+        assert_eq!(loaded.settings(), settings(7));
+        assert_eq!(loaded.high_scores(Scoring::Classic).entries(), &[HighScore::new(Scoring::Classic, 30, *b"xyz"), HighScore::new(Scoring::Classic, 12, *b"abc")]);
+    }
+
+    /// Offset of the last slot written, in either area.
+    fn last_record(flash: &Flash) -> usize {
+        return (0..BYTES).step_by(RECORD_SIZE as usize).rfind(|at| flash.bytes()[*at] != 0xff).unwrap();
+    }
+
+    #[test]
+    fn torn_record_is_skipped() {
+        let mut store: Store<Flash, 3> = Store::new(Flash::new());
+        store.save_settings(settings(7)).unwrap();
+        store.add_score(HighScore::new(Scoring::Classic, 12, *b"abc")).unwrap();
+        let at = last_record(store.storage());
+        // The write stopped half way, leaving the rest erased.
+        store.storage().bytes_mut()[at + 8..at + RECORD_SIZE as usize].fill(0xff);
+        store.save_settings(settings(9)).unwrap();
+        let loaded = reload(store.storage());
+        assert!(loaded.high_scores(Scoring::Classic).is_empty());
+        assert_eq!(loaded.settings(), settings(9));
+    }
+
+    #[test]
+    fn bad_crc_is_skipped() {
+        let mut store: Store<Flash, 3> = Store::new(Flash::new());
+        store.save_settings(settings(7)).unwrap();
+        store.save_settings(settings(9)).unwrap();
+        let at = last_record(store.storage());
+        store.storage().bytes_mut()[at + 5] ^= 0x01;
+        assert_eq!(reload(store.storage()).settings(), settings(7));
+    }
+
+    #[test]
+    fn compaction_keeps_the_table_and_the_settings() {
+        let mut store: Store<Flash, 3> = Store::new(Flash::new());
+        store.save_settings(settings(7)).unwrap();
+        // Far more records than one area holds.
+        for score in 1..=20 { store.add_score(HighScore::new(Scoring::Classic, score, *b"abc")).unwrap(); }
+        assert_eq!(scores(&store), [20, 19, 18]);
+        let loaded = reload(store.storage());
+        assert_eq!(scores(&loaded), [20, 19, 18]);
+        assert_eq!(loaded.settings(), settings(7));
+    }
+
+    /// Storage that loses power after `budget` more writes and erases; the
+    /// one it loses power in gets half done.
+    struct PowerCut<'a> {
+        flash: &'a mut Flash,
+        budget: usize,
+    }
+
+    impl<'a> Storage for PowerCut<'a> {
+        type Error = OutOfBounds;
+
+        fn capacity(&self) -> u32 { return self.flash.capacity(); }
+
+        fn read(&mut self, offset: u32, buf: &mut [u8]) -> Result<(), OutOfBounds> { return self.flash.read(offset, buf); }
+
+        fn write(&mut self, offset: u32, data: &[u8]) -> Result<(), OutOfBounds> {
+            if self.budget == 0 {
+                self.flash.write(offset, &data[..data.len() / 2])?;
+                return Err(OutOfBounds);
+            }
+            self.budget -= 1;
+            return self.flash.write(offset, data);
+        }
+
+        fn erase(&mut self, offset: u32, len: u32) -> Result<(), OutOfBounds> {
+            if self.budget == 0 {
+                self.flash.erase(offset, len / 2)?;
+                return Err(OutOfBounds);
+            }
+            self.budget -= 1;
+            return self.flash.erase(offset, len);
+        }
+    }
+
+    #[test]
+    fn power_loss_during_compaction_keeps_the_old_area() {
+        // Fill the first area: scores 3..=5, the settings, the `AREA` record
+        // and 3 settings changes.
+        let mut full: Store<Flash, 3> = Store::new(Flash::new());
+        for score in 3..=5 { full.add_score(HighScore::new(Scoring::Classic, score, *b"abc")).unwrap(); }
+        for difficulty in [3, 4, 5] { full.save_settings(settings(difficulty)).unwrap(); }
+        let before = *full.storage().bytes();
+        for budget in 0.. {
+            let mut flash = Flash::new();
+            *flash.bytes_mut() = before;
+            let done = {
+                let mut store: Store<PowerCut, 3> = Store::new(PowerCut { flash: &mut flash, budget });
+                store.load().unwrap();
+                assert_eq!(scores(&store), [5, 4, 3]);
+                store.add_score(HighScore::new(Scoring::Classic, 9, *b"new")).is_ok()
+            };
+            let loaded = reload(&flash);
+            assert_eq!(loaded.settings(), settings(5), "budget {}", budget);
+            if done {
+                assert_eq!(scores(&loaded), [9, 5, 4]);
+                break;
+            }
+            assert_eq!(scores(&loaded), [5, 4, 3], "budget {}", budget);
+        }
+    }
+
+    #[test]
+    fn each_scoring_has_its_own_table() {
+        let mut store: Store<Flash, 3> = Store::new(Flash::new());
+        store.add_score(HighScore::new(Scoring::Timed, 12, *b"abc")).unwrap();
+        store.add_score(HighScore::new(Scoring::Simon, 7, *b"xyz")).unwrap();
+        let loaded = reload(store.storage());
+        assert!(loaded.high_scores(Scoring::Classic).is_empty());
+        assert_eq!(loaded.high_scores(Scoring::Timed).entries(), &[HighScore::new(Scoring::Timed, 12, *b"abc")]);
+        assert_eq!(loaded.high_scores(Scoring::Simon).entries(), &[HighScore::new(Scoring::Simon, 7, *b"xyz")]);
+    }
+
+    #[test]
+    fn newest_area_wins() {
+        let mut store: Store<Flash, 3> = Store::new(Flash::new());
+        // Two compactions: both areas end up complete.
+        for difficulty in 1..=16 { store.save_settings(settings(difficulty)).unwrap(); }
+        let (area, generation) = store.active.unwrap();
+        assert!(generation >= 3);
+        let loaded = reload(store.storage());
+        assert_eq!(loaded.active, Some((area, generation)));
+        assert_eq!(loaded.settings(), settings(16));
+    }
+}
@@ -0,0 +1,21 @@
+use embassy_stm32::adc::{Adc, SampleTime};
+use embassy_stm32::peripherals::ADC1;
+use embassy_time::{Delay, Instant};
+
+/// Boot seed for `memory_core::Rng`.
+///
+/// Folds the low, noisy bits of repeated internal temperature sensor and
+/// Vrefint readings together with the time since reset. The ADC is handed
+/// back to the chip when done.
+pub(crate) fn boot_seed(adc: ADC1) -> u64 {
+    let mut adc = Adc::new(adc, &mut Delay);
+    adc.set_sample_time(SampleTime::Cycles3);
+    let mut temperature = adc.enable_temperature();
+    let mut vrefint = adc.enable_vrefint();
+    let mut seed: u64 = Instant::now().as_ticks();
+    for _ in 0..64 {
+        let noise = adc.read(&mut temperature) as u64 ^ ((adc.read(&mut vrefint) as u64) << 8);
+        seed = seed.rotate_left(7) ^ noise;
+    }
+    return seed;
+}
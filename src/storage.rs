@@ -0,0 +1,37 @@
+use embassy_stm32::flash::{Blocking, Error, Flash};
+use memory_core::Storage;
+
+/// Sectors 6 and 7, the last 256K of the F411's flash, one for each area
+/// of the `Store`. `memory.x` keeps the firmware out of them.
+pub(crate) const STORE_OFFSET: u32 = 0x4_0000;
+pub(crate) const STORE_SIZE: u32 = 2 * 128 * 1024;
+
+/// Whole erase sectors of the internal flash, as `Storage`.
+pub(crate) struct FlashArea<'d> {
+    flash: Flash<'d, Blocking>,
+    offset: u32,
+    size: u32,
+}
+
+impl<'d> FlashArea<'d> {
+    /// `offset` and `size` must cover whole sectors.
+    pub(crate) fn new(flash: Flash<'d, Blocking>, offset: u32, size: u32) -> Self { Self { flash, offset, size } }
+}
+
+impl<'d> Storage for FlashArea<'d> {
+    type Error = Error;
+
+    fn capacity(&self) -> u32 { return self.size; }
+
+    fn read(&mut self, offset: u32, buf: &mut [u8]) -> Result<(), Error> {
+        return self.flash.blocking_read(self.offset + offset, buf);
+    }
+
+    fn write(&mut self, offset: u32, data: &[u8]) -> Result<(), Error> {
+        return self.flash.blocking_write(self.offset + offset, data);
+    }
+
+    fn erase(&mut self, offset: u32, len: u32) -> Result<(), Error> {
+        return self.flash.blocking_erase(self.offset + offset, self.offset + offset + len);
+    }
+}